@@ -19,7 +19,15 @@ type RubikDefinition = polyhedron::definition_cube_2x2::Cube2x2Definition;
 pub fn start_bevy() {
 
     fn setup_scene(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
-        let mut polyhedron = Polyhedron::create_polyhedron(RubikDefinition::new());
+        let definition = match RubikDefinition::try_new() {
+            Ok(definition) => definition,
+            Err(error) => {
+                error!("Invalid polyhedron definition: {}", error);
+                return;
+            }
+        };
+
+        let mut polyhedron = Polyhedron::create_polyhedron(definition);
         setup_polyhedron_mesh(&mut commands, &mut meshes, &mut materials, polyhedron);
 
         let translation = Vec3::new(-2.0, 2.5, 5.0);
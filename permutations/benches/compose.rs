@@ -0,0 +1,62 @@
+//! Micro benchmark of `Permutation::compose` against `CompactPermutation::compose_into`.
+//!
+//! Run with `cargo bench --bench compose`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use permutations::compact::SmallPermutation;
+use permutations::{compose, Permutation};
+
+const ITERATIONS: usize = 1_000_000;
+
+/// Same moves as the `cube_2x2_moves` fixture of the library tests, which is only compiled for them.
+fn cube_2x2_moves() -> Vec<Permutation> {
+    return vec![
+        Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]),
+        Permutation::create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]]),
+        Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]),
+        Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]),
+        Permutation::create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]]),
+        Permutation::create_permutation("b".to_string(), vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]]),
+    ];
+}
+
+fn bench_permutation(moves: &[Permutation]) -> (Duration, Permutation) {
+    let start = Instant::now();
+
+    let mut state = Permutation::identity();
+    for i in 0..ITERATIONS {
+        state = compose(&state, black_box(&moves[i % moves.len()]));
+    }
+
+    return (start.elapsed(), state);
+}
+
+fn bench_compact(moves: &[SmallPermutation<55>]) -> (Duration, SmallPermutation<55>) {
+    let start = Instant::now();
+
+    let mut state = SmallPermutation::<55>::identity();
+    let mut next = state;
+    for i in 0..ITERATIONS {
+        state.compose_into(black_box(&moves[i % moves.len()]), &mut next);
+        std::mem::swap(&mut state, &mut next);
+    }
+
+    return (start.elapsed(), state);
+}
+
+fn main() {
+    // Names are dropped so that the words don't grow with the number of iterations
+    let moves: Vec<Permutation> = cube_2x2_moves().into_iter().map(|mut m| { m.clear_name(); m }).collect();
+    let compact_moves: Vec<SmallPermutation<55>> = moves.iter().map(|m| SmallPermutation::try_from(m).unwrap()).collect();
+
+    let (permutation_time, permutation_state) = bench_permutation(&moves);
+    let (compact_time, compact_state) = bench_compact(&compact_moves);
+    assert_eq!(Permutation::from(&compact_state), permutation_state);
+
+    let per_compose = |time: Duration| time.as_nanos() as f64 / ITERATIONS as f64;
+    println!("Permutation::compose            {:>8.1} ns/compose", per_compose(permutation_time));
+    println!("CompactPermutation::compose_into {:>8.1} ns/compose", per_compose(compact_time));
+    println!("speedup                          {:>8.1}x", permutation_time.as_secs_f64() / compact_time.as_secs_f64());
+}
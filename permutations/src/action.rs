@@ -0,0 +1,223 @@
+//! Action homomorphisms: the permutation induced on an orbit or on a block system.
+//!
+//! Restricting a puzzle to some of its pieces, e.g. the corner permutation without the orientations,
+//! maps every state to a smaller permutation, where the i-th point or block of the domain is numbered i.
+//! The elements mapped to the identity form the kernel of the homomorphism, e.g. the corner twists:
+//! ```
+//! use permutations::{BigUint, Permutation};
+//! use permutations::action::ActionHomomorphism;
+//! use permutations::block::smallest_blocks;
+//! use permutations::group::PermutationGroup;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//! let u = Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]);
+//! let group = PermutationGroup::create_group(vec![&f, &r, &u]);
+//!
+//! let corners = ActionHomomorphism::create_block_action(smallest_blocks(1, vec![&f, &r, &u]));
+//! assert_eq!(corners.apply(&f).cycles(), vec![vec![0, 3, 2, 1]]);
+//! assert_eq!(corners.image(&group).order(), BigUint::from(5_040u32));
+//! assert_eq!(corners.kernel(&group).order(), BigUint::from(729u32));
+//! ```
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::error::PermutationError;
+use crate::group::PermutationGroup;
+use crate::Permutation;
+use crate::word::Word;
+
+pub struct ActionHomomorphism {
+    domain: Vec<Vec<usize>>,
+
+    // index[x] is the index in the domain of the orbit point or block containing x
+    index: Vec<Option<usize>>,
+}
+
+/// The image group of an action, whose generators are the images of the generators of the group.
+struct NamedImage {
+    group: PermutationGroup,
+
+    // Whether the generators are named after the generators of the group rather than after their index
+    named: bool,
+}
+
+impl ActionHomomorphism {
+    /// Action on the specified points, usually an orbit, the i-th point being numbered i.
+    pub fn create_orbit_action(points: Vec<usize>) -> Self {
+        return Self::create_block_action(points.into_iter().map(|point| vec![point]).collect());
+    }
+
+    /// Action on the specified blocks, e.g. the pieces given by `smallest_blocks`, the i-th block being numbered i.
+    pub fn create_block_action(blocks: Vec<Vec<usize>>) -> Self {
+        let degree = blocks.iter().flatten().max().map_or(0, |max| max + 1);
+
+        let mut index = vec![None; degree];
+        for (i, block) in blocks.iter().enumerate() {
+            for &point in block {
+                index[point] = Some(i);
+            }
+        }
+
+        return Self { domain: blocks, index };
+    }
+
+    /// Returns the points or blocks acted on, the i-th one being numbered i in the images.
+    pub fn domain(&self) -> &Vec<Vec<usize>> {
+        return &self.domain;
+    }
+
+    /// Returns the permutation of `0..n` induced on the n points or blocks of the domain, keeping the name.
+    /// Returns an error if a point or block is not sent onto a point or block of the domain.
+    pub fn try_apply(&self, permutation: &Permutation) -> Result<Permutation, PermutationError> {
+        let mut one_line = Vec::with_capacity(self.domain.len());
+
+        for block in &self.domain {
+            let image = permutation.apply(block[0]);
+            let target = self.index_of(image).ok_or(PermutationError::SubsetNotPreserved { point: block[0], image })?;
+
+            for &point in block {
+                let image = permutation.apply(point);
+                if self.index_of(image) != Some(target) {
+                    return Err(PermutationError::SubsetNotPreserved { point, image });
+                }
+            }
+
+            one_line.push(target);
+        }
+
+        let image = Permutation::try_from_one_line(one_line)?;
+
+        return Ok(match permutation.word() {
+            Some(word) => image.with_word(word.clone()),
+            None => image,
+        });
+    }
+
+    /// Same as `try_apply`, but panics if the domain is not preserved.
+    pub fn apply(&self, permutation: &Permutation) -> Permutation {
+        return self.try_apply(permutation).unwrap_or_else(|error| panic!("Domain not preserved by {}: {}", permutation, error));
+    }
+
+    /// Returns the group induced on the domain, generated by the images of the generators.
+    pub fn image(&self, group: &PermutationGroup) -> PermutationGroup {
+        let images: Vec<Permutation> = group.generators().iter().map(|g| self.apply(g)).collect();
+
+        return PermutationGroup::create_group(images.iter().collect());
+    }
+
+    /// Returns an element of the group with the specified image, or None if it is not in the image of the group.
+    /// It is named after the generators of the group when they are all named generators, like puzzle moves.
+    pub fn preimage(&self, group: &PermutationGroup, image: &Permutation) -> Option<Permutation> {
+        return self.preimage_in(group, &self.named_image(group), image);
+    }
+
+    /// Returns the kernel, i.e. the elements of the group leaving every point or block of the domain in place.
+    /// It is generated by the elements `g * h^-1` for random elements g of the group, h having the same image as g,
+    /// until its order is the order of the group divided by the order of the image. Its generators are unnamed.
+    pub fn kernel(&self, group: &PermutationGroup) -> PermutationGroup {
+        let image = self.named_image(group);
+        let kernel_order = group.order() / image.group.order();
+
+        let mut kernel = PermutationGroup::create_group(vec![]);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        while kernel.order() < kernel_order {
+            let g = group.random_element(&mut rng);
+            let h = self.preimage_in(group, &image, &self.apply(&g)).unwrap();
+            let mut element = &g * &h.inverse();
+            element.clear_name();
+
+            kernel.add_generator(&element);
+        }
+
+        return kernel;
+    }
+
+    /// Returns the group induced on the domain, its generators being named so that they can be found back
+    /// in the factorizations: after the generators of the group when they are named generators, otherwise after their index.
+    fn named_image(&self, group: &PermutationGroup) -> NamedImage {
+        let named = group.generators().iter().all(|g| matches!(g.word(), Some(Word::Generator(_))));
+
+        let images: Vec<Permutation> = group.generators().iter().enumerate()
+            .map(|(i, g)| match named {
+                true => self.apply(g),
+                false => Permutation::create_permutation_one_line(i.to_string(), self.apply(g).one_line_permutation),
+            })
+            .collect();
+
+        return NamedImage { group: PermutationGroup::create_group(images.iter().collect()), named };
+    }
+
+    fn preimage_in(&self, group: &PermutationGroup, image: &NamedImage, element: &Permutation) -> Option<Permutation> {
+        let word = image.group.factorize_word(element)?;
+
+        let lookup = |name: &str| match image.named {
+            true => group.generators().iter().find(|g| g.name().as_deref() == Some(name)),
+            false => name.parse::<usize>().ok().and_then(|i| group.generators().get(i)),
+        };
+        let mut preimage = word.evaluate(&lookup)?;
+
+        if !image.named {
+            preimage.clear_name();
+        }
+
+        return Some(preimage);
+    }
+
+    fn index_of(&self, point: usize) -> Option<usize> {
+        return self.index.get(point).copied().flatten();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::action::ActionHomomorphism;
+    use crate::block::smallest_blocks;
+    use crate::error::PermutationError;
+    use crate::group::PermutationGroup;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_corner_permutation() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+        let corners = ActionHomomorphism::create_block_action(smallest_blocks(1, moves.iter().collect()));
+
+        let fr = &moves[0] * &moves[3];
+        assert_eq!(corners.apply(&fr), &corners.apply(&moves[0]) * &corners.apply(&moves[3]));
+        assert_eq!(corners.apply(&fr).name().unwrap(), "f r");
+
+        let image = corners.image(&group);
+        assert_eq!(image.order(), BigUint::from(40_320u32));
+
+        // Only the twists are left, 3^7 of them
+        let kernel = corners.kernel(&group);
+        assert_eq!(kernel.order(), BigUint::from(2_187u32));
+        assert!(kernel.generators().iter().all(|k| group.contains(k) && corners.apply(k).is_identity));
+
+        let preimage = corners.preimage(&group, &corners.apply(&fr)).unwrap();
+        assert_eq!(corners.apply(&preimage), corners.apply(&fr));
+        assert_eq!(preimage.name().unwrap(), "f r");
+        assert_eq!(corners.preimage(&PermutationGroup::create_group(vec![&moves[0]]), &corners.apply(&moves[3])), None);
+    }
+
+    #[test]
+    fn test_orbit_action() {
+        let moves = cube_2x2_moves();
+        let f = &moves[0];
+        let front = ActionHomomorphism::create_orbit_action(vec![1, 2, 3, 4]);
+
+        assert_eq!(front.apply(f), Permutation::from_cycles(vec![vec![0, 3, 2, 1]]));
+        assert_eq!(front.domain().len(), 4);
+        assert_eq!(front.try_apply(&moves[3]), Err(PermutationError::SubsetNotPreserved { point: 2, image: 32 }));
+
+        // Turning f and b, the kernel on the front face is made of the b turns
+        let group = PermutationGroup::create_group(vec![f, &moves[5]]);
+        let kernel = front.kernel(&group);
+        assert_eq!(kernel.order(), BigUint::from(4u32));
+        assert!(kernel.contains(&moves[5]));
+    }
+}
@@ -0,0 +1,195 @@
+//! Block systems of the action of a group on an orbit.
+//!
+//! A block is a set of points that the moves always send together: either the whole block goes onto
+//! another block, or onto itself. The facelets of a piece form a block, e.g. 1, 14 and 42 for a corner of the 2x2.
+//! The blocks of a block system partition the orbit, and the finest non trivial system gives the pieces:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::block::{block_system, smallest_blocks};
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//!
+//! assert!(smallest_blocks(1, vec![&f, &r]).contains(&vec![1, 14, 42]));
+//! assert!(block_system(1, 14, vec![&f, &r]).contains(&vec![1, 14, 42]));
+//! ```
+
+use crate::group::PermutationGroup;
+use crate::orbit::orbit;
+use crate::Permutation;
+
+/// Returns the finest block system where a and b are in the same block, as a partition of the orbit of a.
+/// Every block is sorted, and the blocks are sorted by smallest point.
+/// If b is not in the orbit of a, there is no such system and the orbit of a is returned as a single block.
+pub fn block_system(a: usize, b: usize, generators: Vec<&Permutation>) -> Vec<Vec<usize>> {
+    let points = orbit(a, generators.clone());
+    let degree = points.iter().max().map_or(0, |max| max + 1).max(b + 1);
+
+    if !points.contains(&b) {
+        return vec![sorted(points)];
+    }
+
+    // Atkinson's algorithm: when x and y are in the same block, so are g(x) and g(y)
+    let mut classes = UnionFind::create_union_find(degree);
+    let mut pairs = vec![(a, b)];
+    classes.union(a, b);
+
+    while let Some((x, y)) = pairs.pop() {
+        for generator in &generators {
+            let (gx, gy) = (generator.apply(x), generator.apply(y));
+
+            if classes.union(gx, gy) {
+                pairs.push((gx, gy));
+            }
+        }
+    }
+
+    let mut blocks: Vec<Vec<usize>> = Vec::new();
+    let mut block_of_root: Vec<Option<usize>> = vec![None; degree];
+    for point in sorted(points) {
+        let root = classes.find(point);
+
+        match block_of_root[root] {
+            Some(i) => blocks[i].push(point),
+            None => {
+                block_of_root[root] = Some(blocks.len());
+                blocks.push(vec![point]);
+            }
+        }
+    }
+
+    return blocks;
+}
+
+/// Returns the smallest block containing both points, see `block_system`.
+pub fn minimal_block(a: usize, b: usize, generators: Vec<&Permutation>) -> Vec<usize> {
+    return block_system(a, b, generators).into_iter()
+        .find(|block| block.contains(&a))
+        .unwrap();
+}
+
+/// Returns the block system of the orbit of the point with the smallest non trivial blocks, i.e. the pieces,
+/// or the orbit split in single points if the action on the orbit is primitive.
+/// When several systems have blocks of the same size, the one found with the smallest point is returned.
+pub fn smallest_blocks(point: usize, generators: Vec<&Permutation>) -> Vec<Vec<usize>> {
+    let points = sorted(orbit(point, generators.clone()));
+
+    let mut smallest: Option<Vec<Vec<usize>>> = None;
+    for &other in &points {
+        if other == point {
+            continue;
+        }
+
+        let system = block_system(point, other, generators.clone());
+        let block_size = system[0].len();
+
+        if block_size < points.len() && smallest.as_ref().is_none_or(|blocks| block_size < blocks[0].len()) {
+            smallest = Some(system);
+        }
+    }
+
+    return smallest.unwrap_or_else(|| points.iter().map(|&x| vec![x]).collect());
+}
+
+impl PermutationGroup {
+    /// Returns true if the group is transitive on the orbit of the point and has no non trivial block system there.
+    pub fn is_primitive_on_orbit(&self, point: usize) -> bool {
+        return smallest_blocks(point, self.generators().iter().collect()).iter().all(|block| block.len() == 1);
+    }
+
+    /// Returns the smallest block containing both points, see `block_system`.
+    pub fn minimal_block(&self, a: usize, b: usize) -> Vec<usize> {
+        return minimal_block(a, b, self.generators().iter().collect());
+    }
+}
+
+fn sorted(mut points: Vec<usize>) -> Vec<usize> {
+    points.sort_unstable();
+
+    return points;
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn create_union_find(size: usize) -> Self {
+        return Self { parent: Vec::from_iter(0..size) };
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut current = x;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+
+        return root;
+    }
+
+    /// Merges the classes of x and y, returns false if they were already the same.
+    fn union(&mut self, x: usize, y: usize) -> bool {
+        let (root_x, root_y) = (self.find(x), self.find(y));
+        if root_x == root_y {
+            return false;
+        }
+
+        self.parent[root_y] = root_x;
+
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::block::{block_system, minimal_block, smallest_blocks};
+    use crate::group::PermutationGroup;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_corners_of_the_2x2() {
+        let moves = cube_2x2_moves();
+
+        let pieces = smallest_blocks(1, moves.iter().collect());
+        assert_eq!(pieces, vec![
+            vec![1, 14, 42], vec![2, 13, 21], vec![3, 24, 32], vec![4, 31, 43],
+            vec![11, 41, 52], vec![12, 22, 51], vec![23, 33, 54], vec![34, 44, 53],
+        ]);
+
+        // f sends 1, 14, 43 to 4, 43, 32, which overlaps the first set without being equal to it
+        assert_eq!(minimal_block(1, 43, moves.iter().collect()).len(), 24);
+        assert_eq!(minimal_block(1, 42, moves.iter().collect()), vec![1, 14, 42]);
+        assert_eq!(block_system(14, 1, moves.iter().collect()), pieces);
+    }
+
+    #[test]
+    fn test_block_systems() {
+        let moves = cube_2x2_moves();
+        let f = &moves[0];
+
+        // Two stickers of different corners are only together in the whole orbit
+        assert_eq!(minimal_block(1, 2, moves.iter().collect()).len(), 24);
+
+        // A single face turn is a cyclic group of order 4 on each of its cycles: {1, 3} and {2, 4} are blocks
+        assert_eq!(block_system(1, 3, vec![f]), vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(smallest_blocks(1, vec![f]), vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(block_system(1, 13, vec![f]), vec![vec![1, 2, 3, 4]]);
+
+        // A 3-cycle is primitive on its 3 points
+        let three_cycle = Permutation::from_cycles(vec![vec![1, 2, 3]]);
+        assert_eq!(smallest_blocks(1, vec![&three_cycle]), vec![vec![1], vec![2], vec![3]]);
+
+        let group = PermutationGroup::create_group(vec![&three_cycle]);
+        assert!(group.is_primitive_on_orbit(1));
+        assert!(!PermutationGroup::create_group(moves.iter().collect()).is_primitive_on_orbit(1));
+        assert_eq!(group.minimal_block(1, 2), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,249 @@
+//! Fixed capacity permutations for hot loops.
+//!
+//! A `CompactPermutation<P, N>` stores the images of the points 0..N in an inline array of `u8` or `u16`,
+//! so it is `Copy` and never allocates: composing and inverting can be done in place with
+//! `compose_into` and `inverse_into`. It has no name, so search code walks compact permutations and converts
+//! from and to `Permutation` at the boundaries, as the breadth first searches of `find_short_conjugator` and
+//! `find_relations` do for up to 256 points:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::compact::SmallPermutation;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let compact_f = SmallPermutation::<55>::try_from(&f).unwrap();
+//!
+//! let mut f2 = SmallPermutation::<55>::identity();
+//! compact_f.compose_into(&compact_f, &mut f2);
+//! assert_eq!(Permutation::from(&f2), f.pow(2));
+//! ```
+//! The composition convention is the same as `compose`: `a.compose(&b)` is a then b, i.e. `a(b(x))`.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::error::PermutationError;
+use crate::Permutation;
+
+/// Integer type used to store the points of a `CompactPermutation`.
+pub trait Point: Copy + Eq + Hash + Debug {
+    /// Number of points that can be stored.
+    const CAPACITY: usize;
+
+    fn from_usize(i: usize) -> Self;
+
+    fn to_usize(self) -> usize;
+}
+
+impl Point for u8 {
+    const CAPACITY: usize = u8::MAX as usize + 1;
+
+    fn from_usize(i: usize) -> Self {
+        return i as u8;
+    }
+
+    fn to_usize(self) -> usize {
+        return self as usize;
+    }
+}
+
+impl Point for u16 {
+    const CAPACITY: usize = u16::MAX as usize + 1;
+
+    fn from_usize(i: usize) -> Self {
+        return i as u16;
+    }
+
+    fn to_usize(self) -> usize {
+        return self as usize;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CompactPermutation<P: Point, const N: usize> {
+    images: [P; N],
+}
+
+/// Up to 256 points, enough for the 2x2 and the 3x3 facelets.
+pub type SmallPermutation<const N: usize> = CompactPermutation<u8, N>;
+
+/// Up to 65536 points, for the big cubes.
+pub type WidePermutation<const N: usize> = CompactPermutation<u16, N>;
+
+impl<P: Point, const N: usize> CompactPermutation<P, N> {
+    // Fails to compile when N points don't fit in P
+    const CAPACITY_CHECK: () = assert!(N <= P::CAPACITY, "Too many points for the point type");
+
+    pub fn identity() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAPACITY_CHECK;
+
+        let mut images = [P::from_usize(0); N];
+        for (i, image) in images.iter_mut().enumerate() {
+            *image = P::from_usize(i);
+        }
+
+        return Self { images };
+    }
+
+    /// Number of points of the permutation, i.e. N.
+    pub fn capacity(&self) -> usize {
+        return N;
+    }
+
+    /// Returns the image of the point, points beyond the capacity being fixed.
+    pub fn apply(&self, i: usize) -> usize {
+        return if i < N { self.images[i].to_usize() } else { i };
+    }
+
+    pub fn is_identity(&self) -> bool {
+        return self.images.iter().enumerate().all(|(i, image)| image.to_usize() == i);
+    }
+
+    /// Writes self then rhs in result, i.e. `result(x) = self(rhs(x))`, without allocating.
+    pub fn compose_into(&self, rhs: &Self, result: &mut Self) {
+        for (image, rhs_image) in result.images.iter_mut().zip(rhs.images.iter()) {
+            *image = self.images[rhs_image.to_usize()];
+        }
+    }
+
+    /// Writes the inverse of self in result, without allocating.
+    pub fn inverse_into(&self, result: &mut Self) {
+        for (i, image) in self.images.iter().enumerate() {
+            result.images[image.to_usize()] = P::from_usize(i);
+        }
+    }
+
+    pub fn compose(&self, rhs: &Self) -> Self {
+        let mut result = *self;
+        self.compose_into(rhs, &mut result);
+
+        return result;
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut result = *self;
+        self.inverse_into(&mut result);
+
+        return result;
+    }
+}
+
+impl<P: Point, const N: usize> TryFrom<&Permutation> for CompactPermutation<P, N> {
+    type Error = PermutationError;
+
+    fn try_from(permutation: &Permutation) -> Result<Self, Self::Error> {
+        if permutation.degree() > N {
+            return Err(PermutationError::DegreeTooLarge { degree: permutation.degree(), capacity: N });
+        }
+
+        let mut result = Self::identity();
+        for (i, image) in result.images.iter_mut().enumerate() {
+            *image = P::from_usize(permutation.apply(i));
+        }
+
+        return Ok(result);
+    }
+}
+
+impl<P: Point, const N: usize> From<&CompactPermutation<P, N>> for Permutation {
+    fn from(permutation: &CompactPermutation<P, N>) -> Self {
+        return Permutation::from_one_line(permutation.images.iter().map(|image| image.to_usize()).collect());
+    }
+}
+
+/// Degree up to which the search code walks `SearchPermutation`s instead of `Permutation`s.
+pub(crate) const SEARCH_CAPACITY: usize = 256;
+
+/// Compact type of the states walked by the search code.
+pub(crate) type SearchPermutation = SmallPermutation<SEARCH_CAPACITY>;
+
+/// States of a search: `CompactPermutation` up to `SEARCH_CAPACITY` points, and `Permutation` beyond.
+pub(crate) trait SearchState: Clone + Eq + Hash {
+    /// Converts the permutation, whose degree must fit the state.
+    fn from_permutation(permutation: &Permutation) -> Self;
+
+    fn then(&self, rhs: &Self) -> Self;
+}
+
+impl<P: Point, const N: usize> SearchState for CompactPermutation<P, N> {
+    fn from_permutation(permutation: &Permutation) -> Self {
+        return Self::try_from(permutation).unwrap();
+    }
+
+    fn then(&self, rhs: &Self) -> Self {
+        return self.compose(rhs);
+    }
+}
+
+impl SearchState for Permutation {
+    fn from_permutation(permutation: &Permutation) -> Self {
+        let mut state = permutation.clone();
+        state.clear_name();
+
+        return state;
+    }
+
+    fn then(&self, rhs: &Self) -> Self {
+        return self.compose(rhs);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::*;
+    use crate::compact::{SmallPermutation, WidePermutation};
+    use crate::error::PermutationError;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_conversions() {
+        let moves = cube_2x2_moves();
+
+        for permutation in &moves {
+            let compact = SmallPermutation::<55>::try_from(permutation).unwrap();
+            assert_eq!(Permutation::from(&compact), *permutation);
+
+            let wide = WidePermutation::<100>::try_from(permutation).unwrap();
+            assert_eq!(Permutation::from(&wide), *permutation);
+        }
+
+        assert_eq!(SmallPermutation::<40>::try_from(&moves[0]),
+                   Err(PermutationError::DegreeTooLarge { degree: 44, capacity: 40 }));
+        assert!(SmallPermutation::<10>::identity().is_identity());
+        assert_eq!(SmallPermutation::<10>::identity().apply(12), 12);
+    }
+
+    #[test]
+    fn test_compose_and_inverse() {
+        let moves = cube_2x2_moves();
+        let (f, r, d) = (&moves[0], &moves[3], &moves[4]);
+        let compact: Vec<SmallPermutation<55>> = moves.iter().map(|m| SmallPermutation::try_from(m).unwrap()).collect();
+        let (compact_f, compact_r, compact_d) = (&compact[0], &compact[3], &compact[4]);
+
+        let mut result = SmallPermutation::<55>::identity();
+        compact_f.compose_into(compact_r, &mut result);
+        assert_eq!(Permutation::from(&result), compose(f, r));
+
+        compact_d.inverse_into(&mut result);
+        assert_eq!(Permutation::from(&result), d.inverse());
+        assert!(compact_d.compose(&compact_d.inverse()).is_identity());
+
+        let a_tech_right = [*compact_f, compact_d.inverse(), compact_f.inverse(), compact_d.inverse(), compact_r.inverse(), *compact_d, *compact_r]
+            .iter()
+            .fold(SmallPermutation::<55>::identity(), |product, m| product.compose(m));
+        assert_eq!(Permutation::from(&a_tech_right), compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r));
+
+        // a_tech_right has order 6
+        let mut power = a_tech_right;
+        let mut next = a_tech_right;
+        let mut seen = HashSet::new();
+        for _ in 1..6 {
+            assert!(seen.insert(power));
+            power.compose_into(&a_tech_right, &mut next);
+            std::mem::swap(&mut power, &mut next);
+        }
+        assert!(power.is_identity());
+    }
+}
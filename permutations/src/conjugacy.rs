@@ -0,0 +1,423 @@
+//! Conjugacy of permutations inside a permutation group.
+//!
+//! Two algorithms a and b are conjugate in a group when there is a setup move c in the group such that
+//! `c.conjugate(&a) == b`, i.e. b is c a c'. Short setup moves are looked for first, then a backtrack search
+//! through the stabilizer chain decides for sure:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::group::PermutationGroup;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//! let u = Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]);
+//! let group = PermutationGroup::create_group(vec![&f, &r, &u]);
+//!
+//! let sexy_move = u.commutator(&r);
+//! let setup = group.find_conjugator(&sexy_move, &f.conjugate(&sexy_move)).unwrap();
+//! assert_eq!(setup.conjugate(&sexy_move), f.conjugate(&sexy_move));
+//! assert_eq!(setup.name().unwrap(), "f");
+//! ```
+//! The elements commuting with a permutation, i.e. its setup moves leaving it unchanged, form its centralizer.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::compact::{SearchPermutation, SearchState, SEARCH_CAPACITY};
+use crate::group::{mul, to_degree, PermutationGroup, StabilizerLevel};
+use crate::Permutation;
+
+/// Setup moves up to this number of moves are looked for by breadth first search before the backtrack search.
+const SHORT_CONJUGATOR_LENGTH: usize = 3;
+
+impl PermutationGroup {
+    /// Returns true if there is an element c of the group such that `c.conjugate(a) == b`.
+    pub fn are_conjugate(&self, a: &Permutation, b: &Permutation) -> bool {
+        let search = ConjugatorSearch::create_search(self, a, b);
+
+        return search.find_conjugator(0, Images::create_images(search.degree)).is_some();
+    }
+
+    /// Returns an element c of the group such that `c.conjugate(a) == b`, named by its word in the generators.
+    /// Setup moves of at most 3 moves are preferred, otherwise the word is not necessarily short.
+    /// Returns None if a and b are not conjugate in the group.
+    pub fn find_conjugator(&self, a: &Permutation, b: &Permutation) -> Option<Permutation> {
+        if let Some(conjugator) = self.find_short_conjugator(a, b, SHORT_CONJUGATOR_LENGTH) {
+            return Some(conjugator);
+        }
+
+        let search = ConjugatorSearch::create_search(self, a, b);
+        let conjugator = Permutation::from_one_line(search.find_conjugator(0, Images::create_images(search.degree))?);
+
+        return self.factorize_word(&conjugator).map(|word| conjugator.with_word(word));
+    }
+
+    /// Returns a shortest conjugator that is a product of at most max_length generators or inverses,
+    /// found by breadth first search, or None if there is no such conjugator.
+    pub fn find_short_conjugator(&self, a: &Permutation, b: &Permutation, max_length: usize) -> Option<Permutation> {
+        if a.cycle_type() != b.cycle_type() {
+            return None;
+        }
+
+        let moves: Vec<Permutation> = self.generators().iter()
+            .flat_map(|g| [g.clone(), g.inverse()])
+            .collect();
+
+        let degree = moves.iter().chain([a, b]).map(|p| p.degree()).max().unwrap_or(0);
+        let path = match degree <= SEARCH_CAPACITY {
+            true => short_conjugator_path::<SearchPermutation>(&moves, a, b, max_length)?,
+            false => short_conjugator_path::<Permutation>(&moves, a, b, max_length)?,
+        };
+
+        return Some(path.into_iter().fold(Permutation::identity(), |conjugator, i| conjugator.compose(&moves[i])));
+    }
+
+    /// Returns the subgroup of the elements commuting with the specified permutation.
+    /// Its generators are named by their words in the generators of this group.
+    pub fn centralizer(&self, a: &Permutation) -> PermutationGroup {
+        let search = ConjugatorSearch::create_search(self, a, a);
+        let levels = self.stabilizer_chain();
+        let mut generators: Vec<Vec<usize>> = Vec::new();
+
+        // The generators found at the deeper levels fix the base points of the upper levels,
+        // so they generate the part of the centralizer already known at the current level.
+        for (i, level) in levels.iter().enumerate().rev() {
+            let mut known_orbit = orbit(level.base_point, &generators);
+
+            for &point in &level.orbit {
+                if known_orbit.contains(&point) {
+                    continue;
+                }
+
+                let mut images = Images::create_images(search.degree);
+                if !images.assign(level.base_point, point, &search.a, &search.b) {
+                    continue;
+                }
+
+                if let Some(element) = search.find_conjugator(i, images) {
+                    generators.push(element);
+                    known_orbit = orbit(level.base_point, &generators);
+                }
+            }
+        }
+
+        let generators: Vec<Permutation> = generators.into_iter()
+            .map(|g| {
+                let g = Permutation::from_one_line(g);
+                match self.factorize_word(&g) {
+                    Some(word) => g.with_word(word),
+                    None => g,
+                }
+            })
+            .collect();
+
+        return PermutationGroup::create_group(generators.iter().collect());
+    }
+}
+
+/// Backtrack search of the elements c of a group such that `c a c^-1 = b`, i.e. `c(a(x)) = b(c(x))` for every x.
+/// The image of a point by c then fixes the image of its whole cycle in a, which must be a cycle of b of the same length.
+struct ConjugatorSearch<'a> {
+    degree: usize,
+    levels: &'a [StabilizerLevel],
+    a: Vec<usize>,
+    b: Vec<usize>,
+
+    // fixed_points[i] are the points fixed by the group of level i, where the partial product is the final element.
+    fixed_points: Vec<Vec<usize>>,
+}
+
+/// The images by the conjugator known so far, with their preimages.
+#[derive(Clone)]
+struct Images {
+    image: Vec<Option<usize>>,
+    preimage: Vec<Option<usize>>,
+}
+
+impl<'a> ConjugatorSearch<'a> {
+    fn create_search(group: &'a PermutationGroup, a: &Permutation, b: &Permutation) -> Self {
+        let degree = group.degree().max(a.degree()).max(b.degree());
+        let levels = group.stabilizer_chain();
+
+        let mut fixed_points: Vec<Vec<usize>> = levels.iter()
+            .map(|level| {
+                let moved: HashSet<usize> = level.generators().iter().flat_map(|g| g.support()).collect();
+                (0..degree).filter(|x| !moved.contains(x)).collect()
+            })
+            .collect();
+        fixed_points.push(Vec::from_iter(0..degree));
+
+        return Self {
+            degree,
+            levels,
+            a: to_degree(a, degree),
+            b: to_degree(b, degree),
+            fixed_points,
+        };
+    }
+
+    /// Looks for a conjugator in the group of the specified level compatible with the specified images.
+    fn find_conjugator(&self, level: usize, images: Images) -> Option<Vec<usize>> {
+        if cycle_type(&self.a) != cycle_type(&self.b) {
+            return None;
+        }
+
+        return self.search(level, Vec::from_iter(0..self.degree), images);
+    }
+
+    /// The conjugator is prefix * g, g being in the group of the specified level.
+    fn search(&self, level: usize, prefix: Vec<usize>, mut images: Images) -> Option<Vec<usize>> {
+        // Points fixed by g are already mapped by prefix
+        for &x in &self.fixed_points[level] {
+            if !images.assign(x, prefix[x], &self.a, &self.b) {
+                return None;
+            }
+        }
+
+        if level == self.levels.len() {
+            return Some(prefix);
+        }
+
+        let current = &self.levels[level];
+        for &point in &current.orbit {
+            let image = prefix[point];
+            if images.image[current.base_point].is_some_and(|forced| forced != image) {
+                continue;
+            }
+
+            let mut next_images = images.clone();
+            if !next_images.assign(current.base_point, image, &self.a, &self.b) {
+                continue;
+            }
+
+            let u = to_degree_vec(&current.transversal(point), self.degree);
+            if let Some(conjugator) = self.search(level + 1, mul(&prefix, &u), next_images) {
+                return Some(conjugator);
+            }
+        }
+
+        return None;
+    }
+}
+
+impl Images {
+    fn create_images(degree: usize) -> Self {
+        return Self {
+            image: vec![None; degree],
+            preimage: vec![None; degree],
+        };
+    }
+
+    /// Maps the point to the image, and the rest of its cycle in a to the matching cycle in b.
+    /// Returns false if it contradicts the images already known or if the cycles have different lengths.
+    fn assign(&mut self, point: usize, image: usize, a: &[usize], b: &[usize]) -> bool {
+        let (mut x, mut y) = (point, image);
+
+        loop {
+            match (self.image[x], self.preimage[y]) {
+                (Some(known_image), _) => return known_image == y,
+                (None, Some(_)) => return false,
+                (None, None) => {
+                    self.image[x] = Some(y);
+                    self.preimage[y] = Some(x);
+                }
+            }
+
+            x = a[x];
+            y = b[y];
+
+            if x == point || y == image {
+                return x == point && y == image;
+            }
+        }
+    }
+}
+
+/// Walks the products of the moves by breadth first search, and returns the indices of the moves of a shortest
+/// product c of at most max_length moves such that c a c' = b, i.e. c a = b c.
+fn short_conjugator_path<S: SearchState>(moves: &[Permutation], a: &Permutation, b: &Permutation, max_length: usize) -> Option<Vec<usize>> {
+    let states: Vec<S> = moves.iter().map(S::from_permutation).collect();
+    let (a, b) = (S::from_permutation(a), S::from_permutation(b));
+    let identity = S::from_permutation(&Permutation::identity());
+
+    let mut visited = HashSet::from([identity.clone()]);
+    let mut queue = VecDeque::from([(identity, Vec::new())]);
+
+    while let Some((conjugator, path)) = queue.pop_front() {
+        if conjugator.then(&a) == b.then(&conjugator) {
+            return Some(path);
+        }
+
+        if path.len() == max_length {
+            continue;
+        }
+
+        for (i, m) in states.iter().enumerate() {
+            let next = conjugator.then(m);
+            if visited.insert(next.clone()) {
+                let mut next_path = path.clone();
+                next_path.push(i);
+                queue.push_back((next, next_path));
+            }
+        }
+    }
+
+    return None;
+}
+
+fn to_degree_vec(one_line: &[usize], degree: usize) -> Vec<usize> {
+    return (0..degree).map(|i| if i < one_line.len() { one_line[i] } else { i }).collect();
+}
+
+fn cycle_type(one_line: &[usize]) -> Vec<usize> {
+    return Permutation::from_one_line(one_line.to_vec()).cycle_type();
+}
+
+/// Orbit of the point under the group generated by the specified elements.
+fn orbit(point: usize, generators: &[Vec<usize>]) -> HashSet<usize> {
+    let mut orbit = HashSet::from([point]);
+    let mut queue = vec![point];
+
+    while let Some(x) = queue.pop() {
+        for generator in generators {
+            if orbit.insert(generator[x]) {
+                queue.push(generator[x]);
+            }
+        }
+    }
+
+    return orbit;
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashSet, VecDeque};
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::*;
+    use crate::group::PermutationGroup;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    fn elements(group: &PermutationGroup) -> Vec<Permutation> {
+        let mut elements = vec![Permutation::identity()];
+        let mut visited = HashSet::from([Permutation::identity()]);
+        let mut queue = VecDeque::from([Permutation::identity()]);
+
+        while let Some(element) = queue.pop_front() {
+            for generator in group.generators() {
+                let next = Permutation::from_one_line((0..group.degree()).map(|i| element.apply(generator.apply(i))).collect());
+                if visited.insert(next.clone()) {
+                    elements.push(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        return elements;
+    }
+
+    #[test]
+    fn test_find_conjugator() {
+        let moves = cube_2x2_moves();
+        let (f, r, d) = (&moves[0], &moves[3], &moves[4]);
+        let group = PermutationGroup::create_group(moves.iter().collect());
+
+        let a_tech_right = compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r);
+        let b = compose(f, &r.inverse()).conjugate(&a_tech_right);
+
+        let setup = group.find_conjugator(&a_tech_right, &b).unwrap();
+        assert_eq!(setup.conjugate(&a_tech_right), b);
+        assert!(setup.word().unwrap().move_count() <= 2);
+
+        assert_eq!(group.find_conjugator(&a_tech_right, &a_tech_right).unwrap(), Permutation::identity());
+
+        // Setup moves too long for the breadth first search
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..5 {
+            let c = group.random_element(&mut rng);
+            let b = c.conjugate(&a_tech_right);
+
+            assert!(group.are_conjugate(&a_tech_right, &b));
+
+            let setup = group.find_conjugator(&a_tech_right, &b).unwrap();
+            assert!(group.contains(&setup));
+            assert_eq!(setup.conjugate(&a_tech_right), b);
+            assert_eq!(setup.word().unwrap().evaluate(&|name: &str| moves.iter().find(|m| m.to_string() == name)).unwrap(), setup);
+        }
+    }
+
+    #[test]
+    fn test_find_short_conjugator_beyond_compact_capacity() {
+        let f = Permutation::create_permutation("f".to_string(), vec![vec![301, 304, 303, 302], vec![313, 342, 331, 324]]);
+        let r = Permutation::create_permutation("r".to_string(), vec![vec![302, 332, 354, 312], vec![321, 324, 323, 322]]);
+        let group = PermutationGroup::create_group(vec![&f, &r]);
+
+        let a = f.commutator(&r);
+        let b = compose(&f, &r).conjugate(&a);
+
+        let setup = group.find_short_conjugator(&a, &b, 2).unwrap();
+        assert_eq!(setup.conjugate(&a), b);
+        assert!(setup.word().unwrap().move_count() <= 2);
+    }
+
+    #[test]
+    fn test_not_conjugate() {
+        let moves = cube_2x2_moves();
+        let (f, l, u, r) = (&moves[0], &moves[1], &moves[2], &moves[3]);
+
+        // f, r, u fix the dlb corner, that l moves
+        let group = PermutationGroup::create_group(vec![f, r, u]);
+        assert!(group.are_conjugate(f, r));
+        assert!(!group.are_conjugate(f, l));
+        assert_eq!(group.find_conjugator(f, l), None);
+        assert!(!group.are_conjugate(f, &f.pow(2)));
+
+        // Whole cube rotations make every face turn conjugate
+        let whole_group = PermutationGroup::create_group(moves.iter().collect());
+        assert!(whole_group.are_conjugate(f, l));
+        assert!(whole_group.are_conjugate(f, &f.inverse()));
+
+        let trivial_group = PermutationGroup::create_group(vec![]);
+        assert!(trivial_group.are_conjugate(f, f));
+        assert!(!trivial_group.are_conjugate(f, r));
+    }
+
+    #[test]
+    fn test_centralizer() {
+        let moves = cube_2x2_moves();
+        let (f, r, b) = (&moves[0], &moves[3], &moves[5]);
+
+        let group = PermutationGroup::create_group(vec![f]);
+        assert_eq!(group.centralizer(f).order(), BigUint::from(4u32));
+
+        // Opposite faces commute
+        let group = PermutationGroup::create_group(vec![f, b]);
+        assert_eq!(group.centralizer(f).order(), BigUint::from(16u32));
+
+        let group = PermutationGroup::create_group(moves.iter().collect());
+        let centralizer = group.centralizer(f);
+        assert!(centralizer.contains(f));
+        assert!(centralizer.contains(b));
+        assert!(!centralizer.contains(r));
+        assert!(centralizer.generators().iter().all(|g| compose(g, f) == compose(f, g) && group.contains(g)));
+    }
+
+    #[test]
+    fn test_centralizer_brute_force() {
+        let moves = cube_2x2_moves();
+        let (f, r) = (&moves[0], &moves[3]);
+        let group = PermutationGroup::create_group(vec![f, r]);
+        let all_elements = elements(&group);
+        assert_eq!(BigUint::from(all_elements.len()), group.order());
+
+        let fr = compose(f, r);
+        for a in [fr.pow(5), f.commutator(r)] {
+            let commuting = all_elements.iter().filter(|c| c.conjugate(&a) == a).count();
+            assert_eq!(group.centralizer(&a).order(), BigUint::from(commuting));
+
+            // The size of the conjugacy class times the size of the centralizer is the order of the group
+            let class: HashSet<Permutation> = all_elements.iter().map(|c| c.conjugate(&a)).collect();
+            assert_eq!(BigUint::from(class.len() * commuting), group.order());
+        }
+    }
+}
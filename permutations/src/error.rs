@@ -0,0 +1,43 @@
+use std::fmt::{Display, Formatter};
+
+/// Reasons why cycles or a one line notation don't describe a bijection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermutationError {
+    /// The point appears twice in the same cycle, e.g. 1 in `[1, 4, 1]`.
+    RepeatedPointInCycle { point: usize, cycle: Vec<usize> },
+    /// The point appears in two different cycles, which are not disjoint.
+    PointInSeveralCycles { point: usize, first_cycle: Vec<usize>, second_cycle: Vec<usize> },
+    /// In one line notation, the image of the point is not a point of the permutation.
+    ImageOutOfRange { point: usize, image: usize },
+    /// In one line notation, two points have the same image.
+    RepeatedImage { image: usize, first_point: usize, second_point: usize },
+    /// The permutation moves points beyond the capacity of a fixed size representation.
+    DegreeTooLarge { degree: usize, capacity: usize },
+    /// The permutation sends a point of a subset, e.g. the positions of some pieces, outside of the subset.
+    SubsetNotPreserved { point: usize, image: usize },
+    /// The states of the pieces are too many for their ranks to fit in a u64, e.g. the permutations of 21 pieces.
+    RankTooLarge { pieces: usize },
+}
+
+impl Display for PermutationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            PermutationError::RepeatedPointInCycle { point, cycle } =>
+                write!(f, "point {} appears more than once in cycle {:?}", point, cycle),
+            PermutationError::PointInSeveralCycles { point, first_cycle, second_cycle } =>
+                write!(f, "point {} appears in cycles {:?} and {:?}", point, first_cycle, second_cycle),
+            PermutationError::ImageOutOfRange { point, image } =>
+                write!(f, "image {} of point {} is out of range", image, point),
+            PermutationError::RepeatedImage { image, first_point, second_point } =>
+                write!(f, "points {} and {} are both mapped to {}", first_point, second_point, image),
+            PermutationError::DegreeTooLarge { degree, capacity } =>
+                write!(f, "degree {} exceeds the capacity of {} points", degree, capacity),
+            PermutationError::SubsetNotPreserved { point, image } =>
+                write!(f, "point {} is sent to {}, outside of the subset", point, image),
+            PermutationError::RankTooLarge { pieces } =>
+                write!(f, "the ranks of the states of {} pieces don't fit in a u64", pieces),
+        };
+    }
+}
+
+impl std::error::Error for PermutationError {}
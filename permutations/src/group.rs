@@ -0,0 +1,838 @@
+//! Permutation groups given by a set of generators.
+//!
+//! The group is stored as a stabilizer chain computed with the Schreier-Sims algorithm:
+//! a base (b_0, ..., b_k) and, for each level i, the group G_i fixing b_0, ..., b_{i-1} together with
+//! the orbit of b_i under G_i, stored as a Schreier vector: the transversal element mapping b_i to a point
+//! of the orbit is read along the tree of the generators that reached the point, rather than stored,
+//! so that a level takes memory linear in the degree for a given number of generators.
+//! The order of the group is then the product of the orbit lengths, and membership is tested by sifting.
+//!
+//! Internally the elements are one line vectors of the same length (the degree of the group),
+//! multiplied with the same convention as `compose`, i.e. `mul(a, b)[x] = a[b[x]]`.
+//! Every strong generator also remembers how it was obtained from the generators of the group,
+//! so that any element can be written back as a word in the generators.
+//!
+//! For the big puzzles, `create_group_randomized` builds the chain from random elements instead,
+//! which stays tractable for degrees in the thousands. Orders are arbitrary-precision integers:
+//! ```
+//! use permutations::{BigUint, Permutation};
+//! use permutations::group::PermutationGroup;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//! let u = Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]);
+//!
+//! let group = PermutationGroup::create_group_randomized(vec![&f, &r, &u], 0);
+//! assert_eq!(group.order(), BigUint::from(3_674_160u32));
+//! ```
+
+use num_bigint::BigUint;
+use rand::{Rng, RngExt};
+
+use crate::{is_identity, Permutation};
+use crate::random::ProductReplacement;
+use crate::word::Word;
+
+/// Number of random elements that must sift through the chain in a row before the randomized chain is accepted.
+/// A random element of the group sifts through an incomplete chain with probability at most 1/2.
+const RANDOMIZED_SIFTS: usize = 40;
+
+pub struct PermutationGroup {
+    degree: usize,
+    generators: Vec<Permutation>,
+    stabilizer_chain: Vec<StabilizerLevel>,
+
+    // The strong generators of a randomized chain don't have words
+    randomized: bool,
+}
+
+/// One level of the stabilizer chain.
+pub struct StabilizerLevel {
+    pub(crate) base_point: usize,
+    generators: Vec<Vec<usize>>,
+    inverse_generators: Vec<Vec<usize>>,
+    generator_words: Vec<Vec<Letter>>,
+    pub(crate) orbit: Vec<usize>,
+
+    // For every point of the orbit but the base point, the generator and the point it was reached from:
+    // the transversal element u_p mapping the base point to p is s * u_q if p was reached from q with s.
+    schreier_tree: Vec<Option<(usize, usize)>>,
+}
+
+/// A generator of the group, or its inverse, in a word.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Letter {
+    generator: usize,
+    inverse: bool,
+}
+
+impl PermutationGroup {
+    pub fn create_group(generators: Vec<&Permutation>) -> Self {
+        let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0);
+
+        let internal_generators: Vec<(Vec<usize>, Vec<Letter>)> = generators.iter()
+            .enumerate()
+            .map(|(i, g)| (to_degree(g, degree), vec![Letter { generator: i, inverse: false }]))
+            .filter(|(g, _)| !is_identity(g))
+            .collect();
+
+        return Self {
+            degree,
+            generators: generators.iter()
+                .map(|&g| g.clone())
+                .collect(),
+            stabilizer_chain: schreier_sims(degree, &internal_generators),
+            randomized: false,
+        };
+    }
+
+    /// Monte Carlo version of `create_group`: the chain is built by sifting random elements, until
+    /// 40 of them in a row sift through. The chain is then complete with probability at least 1 - 2^-40,
+    /// otherwise the order is too small and some elements are not recognized by `contains`.
+    /// Factorizations fall back on `create_group`, so they are as slow as with a deterministic group.
+    pub fn create_group_randomized(generators: Vec<&Permutation>, seed: u64) -> Self {
+        let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0);
+
+        let mut levels: Vec<StabilizerLevel> = Vec::new();
+        for generator in &generators {
+            sift_in(&mut levels, to_degree(generator, degree));
+        }
+
+        let mut random_elements = ProductReplacement::new(generators.clone(), seed);
+        let mut sifted_in_a_row = 0;
+        while sifted_in_a_row < RANDOMIZED_SIFTS {
+            if sift_in(&mut levels, to_degree(&random_elements.random_element(), degree)) {
+                sifted_in_a_row = 0;
+            } else {
+                sifted_in_a_row += 1;
+            }
+        }
+
+        return Self {
+            degree,
+            generators: generators.iter()
+                .map(|&g| g.clone())
+                .collect(),
+            stabilizer_chain: levels,
+            randomized: true,
+        };
+    }
+
+    /// Adds the generator to the group unless it is already an element, extending the stabilizer chain
+    /// rather than computing it again, so that a group can be grown one generator at a time.
+    /// A randomized group is built again by `create_group_randomized`, with the number of generators as seed.
+    /// Returns false if the generator was already an element of the group.
+    pub fn add_generator(&mut self, generator: &Permutation) -> bool {
+        if self.contains(generator) {
+            return false;
+        }
+
+        self.generators.push(generator.clone());
+
+        if self.randomized {
+            *self = PermutationGroup::create_group_randomized(self.generators.iter().collect(), self.generators.len() as u64);
+            return true;
+        }
+
+        if generator.degree() > self.degree {
+            self.degree = generator.degree();
+            for level in &mut self.stabilizer_chain {
+                level.extend_degree(self.degree);
+            }
+        }
+
+        let internal_generator = to_degree(generator, self.degree);
+        let word = vec![Letter { generator: self.generators.len() - 1, inverse: false }];
+
+        // The generator is in the groups of the levels down to the first one whose base point it moves
+        let j = match self.stabilizer_chain.iter().position(|level| internal_generator[level.base_point] != level.base_point) {
+            Some(j) => j,
+            None => {
+                self.stabilizer_chain.push(StabilizerLevel::create_level(first_moved_point(&internal_generator), self.degree));
+                self.stabilizer_chain.len() - 1
+            }
+        };
+
+        for level in &mut self.stabilizer_chain[..=j] {
+            level.add_generator(internal_generator.clone(), word.clone());
+        }
+        complete_chain(&mut self.stabilizer_chain, j + 1, self.degree);
+
+        return true;
+    }
+
+    /// Largest point moved by the generators plus one.
+    pub fn degree(&self) -> usize {
+        return self.degree;
+    }
+
+    pub fn generators(&self) -> &Vec<Permutation> {
+        return &self.generators;
+    }
+
+    pub fn base(&self) -> Vec<usize> {
+        return self.stabilizer_chain.iter().map(|level| level.base_point).collect();
+    }
+
+    /// Returns the strong generating set, i.e. the union of the generators of every level of the chain.
+    pub fn strong_generators(&self) -> Vec<Permutation> {
+        let mut strong_generators: Vec<Vec<usize>> = Vec::new();
+
+        for level in &self.stabilizer_chain {
+            for generator in &level.generators {
+                if !strong_generators.contains(generator) {
+                    strong_generators.push(generator.clone());
+                }
+            }
+        }
+
+        return strong_generators.into_iter()
+            .map(Permutation::from_one_line)
+            .collect();
+    }
+
+    pub fn stabilizer_chain(&self) -> &Vec<StabilizerLevel> {
+        return &self.stabilizer_chain;
+    }
+
+    /// Returns the exact number of elements of the group, i.e. the number of reachable states of a puzzle.
+    pub fn order(&self) -> BigUint {
+        return self.stabilizer_chain.iter()
+            .map(|level| BigUint::from(level.orbit.len()))
+            .product();
+    }
+
+    pub fn is_trivial(&self) -> bool {
+        return self.stabilizer_chain.is_empty();
+    }
+
+    /// Returns true if the specified permutation can be written as a product of the generators.
+    pub fn contains(&self, permutation: &Permutation) -> bool {
+        if permutation.degree() > self.degree {
+            return false;
+        }
+
+        let (residue, level) = strip(&self.stabilizer_chain, to_degree(permutation, self.degree), 0);
+
+        return level == self.stabilizer_chain.len() && is_identity(&residue);
+    }
+
+    /// Writes the specified permutation as a sequence of generator names, inverses being suffixed with `i`
+    /// like the puzzle definitions do, e.g. `["f", "di", "fi"]`. Unnamed generators are written in cycle notation.
+    /// Composing them from left to right, as `compose_n` does, gives back the permutation.
+    /// The sequence is not necessarily short.
+    /// Returns None if the permutation is not an element of the group.
+    pub fn factorize(&self, target: &Permutation) -> Option<Vec<String>> {
+        let word = self.factorize_word(target)?;
+
+        let names = word.letters().into_iter()
+            .flat_map(|(name, k)| {
+                let name = if k < 0 { format!("{}i", name) } else { name };
+                std::iter::repeat_n(name, k.unsigned_abs() as usize)
+            })
+            .collect();
+
+        return Some(names);
+    }
+
+    /// Same as `factorize`, but returns the factorization as a word, e.g. `f d' f'`.
+    /// The word is the product of the Schreier tree words of the levels of the stabilizer chain, not reduced
+    /// beyond merging the powers of a generator, so it gets very long for large groups: it is a proof of
+    /// membership rather than a solution to play.
+    /// The composition of the letters is checked against the target in every build, and panics if it differs.
+    pub fn factorize_word(&self, target: &Permutation) -> Option<Word> {
+        if self.randomized {
+            return PermutationGroup::create_group(self.generators.iter().collect()).factorize_word(target);
+        }
+
+        if !self.contains(target) {
+            return None;
+        }
+
+        let mut g = to_degree(target, self.degree);
+        let mut word = Vec::new();
+
+        for level in &self.stabilizer_chain {
+            let point = g[level.base_point];
+
+            append_word(&mut word, &level.transversal_word(point));
+            g = level.strip_point(point, g);
+        }
+
+        let word = shorten_powers(&word, &self.generators);
+
+        let letters: Vec<Permutation> = word.iter()
+            .map(|letter| self.letter_permutation(letter))
+            .collect();
+        let word = letters.iter()
+            .map(|letter| letter.word().cloned().unwrap_or_else(|| Word::Generator(letter.to_string())))
+            .fold(Word::Identity, |word, letter| word.then(&letter));
+        assert_eq!(&crate::compose_n(letters.iter().collect()), target, "Factorization of {} is wrong: {}", target, word);
+
+        return Some(word);
+    }
+
+    /// Returns an element of the group drawn uniformly, unnamed.
+    /// Every element is written in a unique way as a product of one transversal element per level,
+    /// so picking a point uniformly in every orbit gives a uniform element.
+    pub fn random_element<R: Rng>(&self, rng: &mut R) -> Permutation {
+        let mut element = Vec::from_iter(0..self.degree);
+
+        for level in &self.stabilizer_chain {
+            let point = level.orbit[rng.random_range(0..level.orbit.len())];
+            element = mul(&element, &level.transversal(point));
+        }
+
+        return Permutation::from_one_line(element);
+    }
+
+    fn letter_permutation(&self, letter: &Letter) -> Permutation {
+        let generator = &self.generators[letter.generator];
+
+        return if letter.inverse {
+            generator.inverse()
+        } else {
+            generator.clone()
+        };
+    }
+}
+
+impl StabilizerLevel {
+    fn create_level(base_point: usize, degree: usize) -> Self {
+        let mut level = Self {
+            base_point,
+            generators: Vec::new(),
+            inverse_generators: Vec::new(),
+            generator_words: Vec::new(),
+            orbit: Vec::new(),
+            schreier_tree: Vec::new(),
+        };
+        level.compute_orbit(degree);
+
+        return level;
+    }
+
+    fn add_generator(&mut self, generator: Vec<usize>, word: Vec<Letter>) {
+        self.inverse_generators.push(inverse(&generator));
+        self.generators.push(generator);
+        self.generator_words.push(word);
+        self.extend_orbit();
+    }
+
+    /// Adds the fixed points up to the degree to the generators.
+    fn extend_degree(&mut self, degree: usize) {
+        let old_degree = self.schreier_tree.len();
+        let extend = |element: &mut Vec<usize>| element.extend(old_degree..degree);
+
+        self.generators.iter_mut().for_each(extend);
+        self.inverse_generators.iter_mut().for_each(extend);
+        self.schreier_tree.resize(degree, None);
+    }
+
+    fn compute_orbit(&mut self, degree: usize) {
+        self.orbit = vec![self.base_point];
+        self.schreier_tree = vec![None; degree];
+
+        self.extend_orbit();
+    }
+
+    /// Adds the images of the orbit by the generators to the orbit, until it is closed.
+    /// The points already in the orbit keep their place in the Schreier tree, so adding a generator is cheap.
+    fn extend_orbit(&mut self) {
+        let mut i = 0;
+        while i < self.orbit.len() {
+            let point = self.orbit[i];
+
+            for (k, generator) in self.generators.iter().enumerate() {
+                let image = generator[point];
+
+                if !self.contains_point(image) {
+                    self.schreier_tree[image] = Some((k, point));
+                    self.orbit.push(image);
+                }
+            }
+
+            i += 1;
+        }
+    }
+
+    fn contains_point(&self, point: usize) -> bool {
+        return point == self.base_point || self.schreier_tree[point].is_some();
+    }
+
+    /// Transversal element of the specified point of the orbit, i.e. the product `s_1 * ... * s_n`
+    /// of the generators read along the Schreier tree from the point up to the base point.
+    pub(crate) fn transversal(&self, point: usize) -> Vec<usize> {
+        let mut u = Vec::from_iter(0..self.schreier_tree.len());
+
+        let mut current = point;
+        while let Some((k, previous)) = self.schreier_tree[current] {
+            u = mul(&u, &self.generators[k]);
+            current = previous;
+        }
+
+        return u;
+    }
+
+    /// Returns `u^-1 * g` for the transversal element u of the specified point of the orbit, the inverses of
+    /// the generators along the Schreier tree being applied one after the other: `(s * u_q)^-1 * g = u_q^-1 * (s^-1 * g)`.
+    fn strip_point(&self, point: usize, mut g: Vec<usize>) -> Vec<usize> {
+        let mut current = point;
+        while let Some((k, previous)) = self.schreier_tree[current] {
+            g = mul(&self.inverse_generators[k], &g);
+            current = previous;
+        }
+
+        return g;
+    }
+
+    /// Word of the transversal element of the specified point, read along the Schreier tree:
+    /// if u_p = s * u_q then word(u_p) = word(s) word(u_q).
+    fn transversal_word(&self, point: usize) -> Vec<Letter> {
+        let mut word = Vec::new();
+
+        let mut current = point;
+        while let Some((k, previous)) = self.schreier_tree[current] {
+            append_word(&mut word, &self.generator_words[k]);
+            current = previous;
+        }
+
+        return word;
+    }
+
+    pub fn base_point(&self) -> usize {
+        return self.base_point;
+    }
+
+    /// Orbit of the base point under the group of this level, in discovery order.
+    pub fn orbit(&self) -> &Vec<usize> {
+        return &self.orbit;
+    }
+
+    pub fn generators(&self) -> Vec<Permutation> {
+        return self.generators.iter()
+            .map(|g| Permutation::from_one_line(g.clone()))
+            .collect();
+    }
+
+    /// Returns an element of the level group mapping the base point to the specified point,
+    /// or None if the point is not in the orbit.
+    pub fn transversal_element(&self, point: usize) -> Option<Permutation> {
+        if point >= self.schreier_tree.len() || !self.contains_point(point) {
+            return None;
+        }
+
+        return Some(Permutation::from_one_line(self.transversal(point)));
+    }
+}
+
+fn schreier_sims(degree: usize, generators: &Vec<(Vec<usize>, Vec<Letter>)>) -> Vec<StabilizerLevel> {
+    let mut base: Vec<usize> = Vec::new();
+    for (generator, _) in generators {
+        if base.iter().all(|&b| generator[b] == b) {
+            base.push(first_moved_point(generator));
+        }
+    }
+
+    let mut levels: Vec<StabilizerLevel> = base.iter()
+        .map(|&b| StabilizerLevel::create_level(b, degree))
+        .collect();
+
+    // Level i is generated by the generators fixing the first i base points
+    for (generator, word) in generators {
+        for level in levels.iter_mut() {
+            level.generators.push(generator.clone());
+            level.inverse_generators.push(inverse(generator));
+            level.generator_words.push(word.clone());
+
+            if generator[level.base_point] != level.base_point {
+                break;
+            }
+        }
+    }
+
+    for level in levels.iter_mut() {
+        level.compute_orbit(degree);
+    }
+
+    let complete_from = levels.len();
+    complete_chain(&mut levels, complete_from, degree);
+
+    return levels;
+}
+
+/// Runs the Schreier-Sims algorithm on the levels above the specified one, the levels from it being complete,
+/// i.e. their Schreier generators sifting through the levels below them.
+fn complete_chain(levels: &mut Vec<StabilizerLevel>, complete_from: usize, degree: usize) {
+    let mut i = complete_from;
+    while i > 0 {
+        match find_non_sifting_schreier_generator(levels, i - 1) {
+            None => {
+                i -= 1;
+            }
+            Some((residue, word, j)) => {
+                if j == levels.len() {
+                    levels.push(StabilizerLevel::create_level(first_moved_point(&residue), degree));
+                }
+
+                for level in &mut levels[i..=j] {
+                    level.add_generator(residue.clone(), word.clone());
+                }
+
+                i = j + 1;
+            }
+        }
+    }
+}
+
+/// Adds what remains of the element after sifting to the chain, as a strong generator without word.
+/// Returns false if the element sifted through, i.e. if it was already an element of the group of the chain.
+fn sift_in(levels: &mut Vec<StabilizerLevel>, element: Vec<usize>) -> bool {
+    let degree = element.len();
+    let (residue, j) = strip(levels, element, 0);
+
+    if j == levels.len() {
+        if is_identity(&residue) {
+            return false;
+        }
+
+        levels.push(StabilizerLevel::create_level(first_moved_point(&residue), degree));
+    }
+
+    // The residue fixes the base points of the levels above j
+    for level in &mut levels[..=j] {
+        level.add_generator(residue.clone(), Vec::new());
+    }
+
+    return true;
+}
+
+/// Looks for a Schreier generator of the specified level that doesn't sift through the deeper levels.
+/// Returns what remains of it, its word and the level where the sifting stopped.
+fn find_non_sifting_schreier_generator(levels: &[StabilizerLevel], i: usize) -> Option<(Vec<usize>, Vec<Letter>, usize)> {
+    let level = &levels[i];
+
+    for &point in &level.orbit {
+        let u = level.transversal(point);
+
+        for (k, generator) in level.generators.iter().enumerate() {
+            let image = generator[point];
+            let mut schreier_generator = level.strip_point(image, mul(generator, &u));
+
+            if is_identity(&schreier_generator) {
+                continue;
+            }
+
+            let (residue, j) = strip(levels, schreier_generator.clone(), i + 1);
+            if j < levels.len() || !is_identity(&residue) {
+                // Words are only built for the Schreier generators that end up in the chain
+                let mut word = inverse_word(&level.transversal_word(image));
+                append_word(&mut word, &level.generator_words[k]);
+                append_word(&mut word, &level.transversal_word(point));
+
+                for deeper_level in &levels[(i + 1)..j] {
+                    let stripped_point = schreier_generator[deeper_level.base_point];
+                    schreier_generator = deeper_level.strip_point(stripped_point, schreier_generator);
+                    word = concat_words(&inverse_word(&deeper_level.transversal_word(stripped_point)), &word);
+                }
+
+                return Some((residue, word, j));
+            }
+        }
+    }
+
+    return None;
+}
+
+/// Sifts the element through the chain starting at the specified level.
+/// Returns the residue and the index of the level where it left the orbit, or the chain length.
+fn strip(levels: &[StabilizerLevel], element: Vec<usize>, start: usize) -> (Vec<usize>, usize) {
+    let mut g = element;
+
+    for (j, level) in levels.iter().enumerate().skip(start) {
+        let point = g[level.base_point];
+        if !level.contains_point(point) {
+            return (g, j);
+        }
+
+        g = level.strip_point(point, g);
+    }
+
+    return (g, levels.len());
+}
+
+fn inverse_word(word: &[Letter]) -> Vec<Letter> {
+    return word.iter()
+        .rev()
+        .map(|letter| Letter { generator: letter.generator, inverse: !letter.inverse })
+        .collect();
+}
+
+/// Appends the letters one by one, cancelling a letter followed by its inverse.
+fn append_word(word: &mut Vec<Letter>, suffix: &[Letter]) {
+    for &letter in suffix {
+        match word.last() {
+            Some(last) if last.generator == letter.generator && last.inverse != letter.inverse => {
+                word.pop();
+            }
+            _ => word.push(letter),
+        }
+    }
+}
+
+fn concat_words(prefix: &[Letter], suffix: &[Letter]) -> Vec<Letter> {
+    let mut word = prefix.to_vec();
+    append_word(&mut word, suffix);
+
+    return word;
+}
+
+/// Replaces every run of a same generator by the shortest equivalent run, using the order of the generator,
+/// e.g. `f f f` becomes `fi`. Repeated until the word doesn't get shorter, as removing a run can merge two others.
+fn shorten_powers(word: &[Letter], generators: &[Permutation]) -> Vec<Letter> {
+    let mut word = word.to_vec();
+
+    loop {
+        let shortened = shorten_runs(&word, generators);
+        if shortened.len() == word.len() {
+            return shortened;
+        }
+
+        word = shortened;
+    }
+}
+
+fn shorten_runs(word: &[Letter], generators: &[Permutation]) -> Vec<Letter> {
+    let mut shortened = Vec::new();
+
+    let mut i = 0;
+    while i < word.len() {
+        let generator = word[i].generator;
+
+        let mut exponent: i64 = 0;
+        while i < word.len() && word[i].generator == generator {
+            exponent += if word[i].inverse { -1 } else { 1 };
+            i += 1;
+        }
+
+        // A run is shorter than any order that doesn't fit in an i64
+        let order = i64::try_from(generators[generator].order()).unwrap_or(i64::MAX);
+        let mut exponent = exponent.rem_euclid(order);
+        if exponent > order / 2 {
+            exponent -= order;
+        }
+
+        let letter = Letter { generator, inverse: exponent < 0 };
+        for _ in 0..exponent.abs() {
+            append_word(&mut shortened, &[letter]);
+        }
+    }
+
+    return shortened;
+}
+
+pub(crate) fn to_degree(permutation: &Permutation, degree: usize) -> Vec<usize> {
+    return (0..degree).map(|i| permutation.apply(i)).collect();
+}
+
+fn first_moved_point(one_line: &[usize]) -> usize {
+    return (0..one_line.len()).find(|&i| one_line[i] != i).expect("Identity doesn't move any point");
+}
+
+pub(crate) fn mul(a: &[usize], b: &[usize]) -> Vec<usize> {
+    return b.iter().map(|&x| a[x]).collect();
+}
+
+pub(crate) fn inverse(a: &[usize]) -> Vec<usize> {
+    let mut inv = vec![0; a.len()];
+    for (i, &image) in a.iter().enumerate() {
+        inv[image] = i;
+    }
+
+    return inv;
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::*;
+    use crate::group::PermutationGroup;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_trivial_group() {
+        let id = Permutation::identity();
+        let group = PermutationGroup::create_group(vec![&id]);
+
+        assert!(group.is_trivial());
+        assert_eq!(group.order(), BigUint::from(1u32));
+        assert!(group.contains(&id));
+    }
+
+    #[test]
+    fn test_cyclic_group() {
+        let moves = cube_2x2_moves();
+        let f = &moves[0];
+        let group = PermutationGroup::create_group(vec![f]);
+
+        assert_eq!(group.order(), BigUint::from(4u32));
+        assert!(group.contains(&f.inverse()));
+        assert!(group.contains(&f.pow(2)));
+        assert!(!group.contains(&moves[1]));
+    }
+
+    #[test]
+    fn test_cube_2x2_order() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+
+        // 3,674,160 states times the 24 orientations of the whole cube
+        assert_eq!(group.order(), BigUint::from(3_674_160u32 * 24));
+
+        let chain_order: BigUint = group.stabilizer_chain().iter().map(|level| BigUint::from(level.orbit().len())).product();
+        assert_eq!(chain_order, group.order());
+
+        // f, r, u fix the dlb corner, which gives the usual 2x2 group
+        let fixed_corner_group = PermutationGroup::create_group(vec![&moves[0], &moves[3], &moves[2]]);
+        assert_eq!(fixed_corner_group.order(), BigUint::from(3_674_160u32));
+    }
+
+    #[test]
+    fn test_cube_2x2_membership() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+        let (f, r, d) = (&moves[0], &moves[3], &moves[4]);
+
+        let a_tech_right = compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r);
+        assert!(group.contains(&a_tech_right));
+        assert!(group.contains(&f.commutator(r)));
+
+        // swapping two stickers of the same corner is impossible
+        let twisted_sticker = Permutation::create_permutation("swap".to_string(), vec![vec![1, 14]]);
+        assert!(!group.contains(&twisted_sticker));
+
+        // a single corner twist is impossible as well
+        let corner_twist = Permutation::create_permutation("twist".to_string(), vec![vec![1, 14, 43]]);
+        assert!(!group.contains(&corner_twist));
+
+        // points outside of the puzzle
+        let outside = Permutation::create_permutation("outside".to_string(), vec![vec![60, 61]]);
+        assert!(!group.contains(&outside));
+    }
+
+    #[test]
+    fn test_factorize() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+        let (f, r, d) = (&moves[0], &moves[3], &moves[4]);
+
+        let a_tech_right = compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r);
+        let b_tech_right = compose_9(f, &d.inverse(), &f.inverse(), &d.inverse(), &d.inverse(), &r.inverse(), d, d, r);
+
+        let inverses: Vec<Permutation> = moves.iter().map(|m| m.inverse()).collect();
+        let named_moves: HashMap<String, &Permutation> = moves.iter().map(|m| (m.to_string(), m))
+            .chain(moves.iter().zip(inverses.iter()).map(|(m, inverse)| (format!("{}i", m), inverse)))
+            .collect();
+
+        for target in [&a_tech_right, &b_tech_right, f, &f.commutator(r), &compose(f, d).pow(7)] {
+            let names = group.factorize(target).unwrap();
+            let letters: Vec<&Permutation> = names.iter().map(|name| named_moves[name]).collect();
+
+            assert_eq!(compose_n(letters).cycles(), target.cycles());
+        }
+
+        assert_eq!(group.factorize(&Permutation::identity()).unwrap(), Vec::<String>::new());
+        assert_eq!(group.factorize(&f.inverse()).unwrap(), vec!["fi".to_string()]);
+
+        let word = group.factorize_word(&a_tech_right).unwrap();
+        let evaluated = word.evaluate(&|name: &str| moves.iter().find(|m| m.to_string() == name)).unwrap();
+        assert_eq!(evaluated, a_tech_right);
+        assert!(word.move_count() <= group.factorize(&a_tech_right).unwrap().len());
+
+        let twisted_sticker = Permutation::create_permutation("swap".to_string(), vec![vec![1, 14]]);
+        assert_eq!(group.factorize(&twisted_sticker), None);
+    }
+
+    #[test]
+    fn test_add_generator() {
+        let moves = cube_2x2_moves();
+        let (f, r, d) = (&moves[0], &moves[3], &moves[4]);
+
+        let mut group = PermutationGroup::create_group(vec![]);
+        assert!(group.add_generator(f) && !group.add_generator(&f.pow(2)));
+        assert_eq!(group.order(), BigUint::from(4u32));
+
+        // r and d move points beyond the degree of f
+        assert!(group.add_generator(r) && group.add_generator(d));
+        assert_eq!(group.order(), PermutationGroup::create_group(vec![f, r, d]).order());
+        assert_eq!(group.generators().len(), 3);
+
+        let a_tech_right = compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r);
+        let word = group.factorize_word(&a_tech_right).unwrap();
+        assert_eq!(word.evaluate(&|name: &str| moves.iter().find(|m| m.to_string() == name)).unwrap(), a_tech_right);
+    }
+
+    #[test]
+    fn test_stabilizer_chain() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+
+        for level in group.stabilizer_chain() {
+            for &point in level.orbit() {
+                let u = level.transversal_element(point).unwrap();
+                assert_eq!(u.apply(level.base_point()), point);
+            }
+        }
+
+        // each level fixes the base points of the previous ones
+        let base = group.base();
+        for (i, level) in group.stabilizer_chain().iter().enumerate() {
+            for generator in level.generators() {
+                for b in &base[..i] {
+                    assert_eq!(generator.apply(*b), *b);
+                }
+            }
+        }
+
+        for s in group.strong_generators() {
+            assert!(group.contains(&s));
+        }
+    }
+
+    #[test]
+    fn test_randomized_order() {
+        let moves = cube_2x2_moves();
+
+        for seed in 0..5 {
+            let group = PermutationGroup::create_group_randomized(moves.iter().collect(), seed);
+            assert_eq!(group.order(), BigUint::from(3_674_160u32 * 24));
+            assert!(group.contains(&moves[0]));
+        }
+
+        let fixed_corner_group = PermutationGroup::create_group_randomized(vec![&moves[0], &moves[3], &moves[2]], 0);
+        assert_eq!(fixed_corner_group.order(), BigUint::from(3_674_160u32));
+        assert!(!fixed_corner_group.contains(&moves[1]));
+
+        let names = fixed_corner_group.factorize(&compose(&moves[0], &moves[3])).unwrap();
+        assert_eq!(names, vec!["f", "r"]);
+
+        assert_eq!(PermutationGroup::create_group_randomized(vec![], 0).order(), BigUint::from(1u32));
+    }
+
+    #[test]
+    fn test_randomized_big_degree() {
+        // 20 independent 2x2 cubes side by side: degree 1100, and an order overflowing any machine integer
+        let copies = 20;
+        let moves: Vec<Permutation> = (0..copies)
+            .flat_map(|copy| cube_2x2_moves().into_iter().map(move |m| {
+                let cycles = m.cycles().iter().map(|cycle| cycle.iter().map(|x| x + 55 * copy).collect()).collect();
+                Permutation::from_cycles(cycles)
+            }))
+            .collect();
+
+        let group = PermutationGroup::create_group_randomized(moves.iter().collect(), 0);
+        assert_eq!(group.degree(), 55 * copies);
+        assert_eq!(group.order(), BigUint::from(3_674_160u32 * 24).pow(copies as u32));
+    }
+}
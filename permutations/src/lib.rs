@@ -1,35 +1,67 @@
-///
-/// Crate that modelise permutations.
-///
-/// ```
-/// let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
-/// let l = Permutation::create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]]);///
-///
-/// assert_eq!(compose(&f, &l),
-///                    Permutation::create_permutation(
-///                        "f * l".to_string(),
-///                        vec![vec![1, 11, 53, 24, 13, 42, 41, 44, 32, 21, 14, 52, 34, 3, 2], vec![4, 43, 31]])
-///         );
-/// ```
-///
-/// Composition doesn't follow the "standard" in the sense that if you want to run f then g, instead of writing you would write
-/// ```
-/// compose(f, g)
-/// ```
-/// It is equivalent to the mathematical g(f()).
-///
-/// You can create permutation from one line, cycle notation or a composition of permutations.
-///
-///
+//! Crate that modelise permutations, and the groups they generate.
+//!
+//! Permutations can be created from cycle notation, one line notation or composition of other permutations,
+//! and they are composed with `*`, a product reading from left to right: `&f * &l` is f then l.
+//! ```
+//! use permutations::{perm, Permutation};
+//!
+//! let f = perm!("f" => (1 4 3 2)(13 42 31 24)(14 43 32 21));
+//! let l = perm!("l" => (1 11 53 31)(4 14 52 34)(41 44 43 42));
+//!
+//! let fl = &f * &l;
+//! assert_eq!(fl, perm!((1 11 53 24 13 42 41 44 32 21 14 52 34 3 2)(4 43 31)));
+//! assert_eq!(fl.name().unwrap(), "f l");
+//! assert_eq!(fl.apply(1), f.apply(l.apply(1)));
+//! ```
+//!
+//! Composition doesn't follow the "standard" in the sense that running f then g is written `&f * &g`,
+//! whereas it is the mathematical g(f()): the image of a point x is `f.apply(g.apply(x))`.
+//! `-&f` is the inverse of f, `f.pow(k)` its k-th power, and iterators of permutations can be multiplied:
+//! ```
+//! use permutations::{compose, perm, Permutation};
+//!
+//! let f = perm!("f" => (1 4 3 2)(13 42 31 24)(14 43 32 21));
+//! let l = perm!("l" => (1 11 53 31)(4 14 52 34)(41 44 43 42));
+//!
+//! let moves = vec![f.clone(), l.clone(), -&f];
+//! assert_eq!(moves.iter().product::<Permutation>(), compose!(f, l, f.pow(-1)));
+//! ```
+//! The functions `compose` to `compose_9` and `compose_n` do the same.
 
 use std::cmp;
 use std::cmp::max;
 use std::fmt::{Display, Formatter};
-
-
-#[derive(PartialEq, Debug)]
+use std::hash::{Hash, Hasher};
+
+pub mod action;
+pub mod block;
+pub mod compact;
+pub mod conjugacy;
+pub mod error;
+pub mod group;
+pub mod notation;
+pub mod orbit;
+pub mod presentation;
+mod ops;
+pub mod random;
+pub mod rank;
+pub mod relations;
+pub mod series;
+pub mod structure;
+pub mod word;
+
+use error::PermutationError;
+use word::Word;
+
+pub use num_bigint::BigUint;
+
+
+/// Two permutations are equal when they map every point the same way, whatever their names.
+/// The name is only there for display: it is a word saying how the permutation was obtained,
+/// a named permutation being a word made of a single generator.
+#[derive(Clone, Debug)]
 pub struct Permutation {
-    name: String,
+    word: Option<Word>,
 
     one_line_permutation: Vec<usize>,
     is_identity: bool,
@@ -37,6 +69,7 @@ pub struct Permutation {
 }
 
 
+/// Returns p1 then p2, i.e. `p1 * p2`, mapping x to `p1.apply(p2.apply(x))`.
 pub fn compose(p1: &Permutation, p2: &Permutation) -> Permutation {
     let new_degree = max(p1.degree(), p2.degree());
     let mut result_one_line = vec![0; new_degree];
@@ -46,10 +79,15 @@ pub fn compose(p1: &Permutation, p2: &Permutation) -> Permutation {
         result_one_line[i] = p1.apply(p2.apply(i));
     }
 
-    let name = format!("{} * {}", p1.name.clone(), p2.name.clone());
-    return Permutation::create_permutation_one_line(name, result_one_line);
+    let word = match (&p1.word, &p2.word) {
+        (Some(word1), Some(word2)) => Some(word1.then(word2)),
+        _ => None,
+    };
+
+    return Permutation::create_permutation_with_word(word, result_one_line);
 }
 
+/// `compose_2` to `compose_9` are kept for existing callers, `*` and `compose!` are more convenient.
 pub fn compose_2(p1: &Permutation, p2: &Permutation) -> Permutation {
     return compose(p1, p2);
 }
@@ -57,14 +95,14 @@ pub fn compose_2(p1: &Permutation, p2: &Permutation) -> Permutation {
 pub fn compose_3(p1: &Permutation,
                  p2: &Permutation,
                  p3: &Permutation) -> Permutation {
-    return compose(&compose(p1, p2), p3);
+    return [p1, p2, p3].into_iter().product();
 }
 
 pub fn compose_4(p1: &Permutation,
                  p2: &Permutation,
                  p3: &Permutation,
                  p4: &Permutation) -> Permutation {
-    return compose(&compose(&compose(p1, p2), p3), p4);
+    return [p1, p2, p3, p4].into_iter().product();
 }
 
 pub fn compose_5(p1: &Permutation,
@@ -72,7 +110,7 @@ pub fn compose_5(p1: &Permutation,
                  p3: &Permutation,
                  p4: &Permutation,
                  p5: &Permutation) -> Permutation {
-    return compose(&compose(&compose(&compose(p1, p2), p3), p4), p5);
+    return [p1, p2, p3, p4, p5].into_iter().product();
 }
 
 pub fn compose_6(p1: &Permutation,
@@ -81,7 +119,7 @@ pub fn compose_6(p1: &Permutation,
                  p4: &Permutation,
                  p5: &Permutation,
                  p6: &Permutation) -> Permutation {
-    return compose(&compose(&compose(&compose(&compose(p1, p2), p3), p4), p5), p6);
+    return [p1, p2, p3, p4, p5, p6].into_iter().product();
 }
 
 pub fn compose_7(p1: &Permutation,
@@ -91,7 +129,7 @@ pub fn compose_7(p1: &Permutation,
                  p5: &Permutation,
                  p6: &Permutation,
                  p7: &Permutation) -> Permutation {
-    return compose(&compose(&compose(&compose(&compose(&compose(p1, p2), p3), p4), p5), p6), p7);
+    return [p1, p2, p3, p4, p5, p6, p7].into_iter().product();
 }
 
 pub fn compose_8(p1: &Permutation,
@@ -102,7 +140,7 @@ pub fn compose_8(p1: &Permutation,
                  p6: &Permutation,
                  p7: &Permutation,
                  p8: &Permutation) -> Permutation {
-    return compose(&compose(&compose(&compose(&compose(&compose(&compose(p1, p2), p3), p4), p5), p6), p7), p8);
+    return [p1, p2, p3, p4, p5, p6, p7, p8].into_iter().product();
 }
 
 pub fn compose_9(p1: &Permutation,
@@ -114,46 +152,78 @@ pub fn compose_9(p1: &Permutation,
                  p7: &Permutation,
                  p8: &Permutation,
                  p9: &Permutation) -> Permutation {
-    return compose(&compose(&compose(&compose(&compose(&compose(&compose(&compose(p1, p2), p3), p4), p5), p6), p7), p8), p9);
+    return [p1, p2, p3, p4, p5, p6, p7, p8, p9].into_iter().product();
 }
 
 pub fn compose_n(permutations: Vec<&Permutation>) -> Permutation {
-    if permutations.is_empty() {
-        return Permutation::identity();
+    return permutations.into_iter().product();
+}
+
+
+impl Permutation {
+    pub fn identity() -> Self {
+        return Permutation::create_permutation_with_word(Some(Word::Identity), vec![]);
     }
 
-    let first_element = permutations[0];
-    let mut result = Permutation::create_permutation_one_line(first_element.name.clone(), first_element.one_line_permutation.clone());
+    /// Panics if the one line notation is not a bijection, see `try_create_permutation_one_line`.
+    pub fn create_permutation_one_line(name: String, one_line_notation: Vec<usize>) -> Self {
+        return Permutation::try_create_permutation_one_line(name, one_line_notation)
+            .unwrap_or_else(|error| panic!("Illegal use of one line notation: {}", error));
+    }
 
-    if permutations.len() > 1 {
-        for permutation in &permutations[1..] {
-            result = compose(&result, permutation);
-        }
+    /// Creates a permutation from its one line notation, checking that every image is a point
+    /// and that no point is the image of two points.
+    pub fn try_create_permutation_one_line(name: String, one_line_notation: Vec<usize>) -> Result<Self, PermutationError> {
+        check_one_line(&one_line_notation)?;
+
+        return Ok(Permutation::create_permutation_with_word(Some(Word::Generator(name)), one_line_notation));
     }
 
-    return result;
-}
+    /// Creates an unnamed permutation from its one line notation.
+    /// Panics if the one line notation is not a bijection, see `try_from_one_line`.
+    pub fn from_one_line(one_line_notation: Vec<usize>) -> Self {
+        return Permutation::try_from_one_line(one_line_notation)
+            .unwrap_or_else(|error| panic!("Illegal use of one line notation: {}", error));
+    }
 
+    pub fn try_from_one_line(one_line_notation: Vec<usize>) -> Result<Self, PermutationError> {
+        check_one_line(&one_line_notation)?;
 
-impl Permutation {
-    pub fn identity() -> Self {
-        return Permutation::create_permutation_one_line("id".to_string(), vec![]);
+        return Ok(Permutation::create_permutation_with_word(None, one_line_notation));
     }
 
-    pub fn create_permutation_one_line(name: String, one_line_notation: Vec<usize>) -> Self {
+    /// Creates an unnamed permutation from its cycle notation.
+    /// Panics if the cycles are not disjoint, see `try_from_cycles`.
+    pub fn from_cycles(cycles: Vec<Vec<usize>>) -> Self {
+        return Permutation::from_one_line(convert_cycles_to_one_line(&cycles));
+    }
+
+    pub fn try_from_cycles(cycles: Vec<Vec<usize>>) -> Result<Self, PermutationError> {
+        return Ok(Permutation::create_permutation_with_word(None, try_convert_cycles_to_one_line(&cycles)?));
+    }
+
+    fn create_permutation_with_word(word: Option<Word>, one_line_notation: Vec<usize>) -> Self {
         return Self {
-            name,
+            word,
             is_identity: is_identity(&one_line_notation),
             internal_degree: internal_degree(&one_line_notation),
             one_line_permutation: one_line_notation,
         };
     }
 
+    /// Panics if the cycles are not disjoint, see `try_create_permutation`.
     pub fn create_permutation(name: String, cycles: Vec<Vec<usize>>) -> Self {
         let one_line_notation = convert_cycles_to_one_line(&cycles);
         return Permutation::create_permutation_one_line(name, one_line_notation);
     }
 
+    /// Creates a permutation from its cycle notation, checking that no point appears twice in the cycles.
+    /// Cycles of length one are fixed points.
+    pub fn try_create_permutation(name: String, cycles: Vec<Vec<usize>>) -> Result<Self, PermutationError> {
+        let one_line_notation = try_convert_cycles_to_one_line(&cycles)?;
+        return Ok(Permutation::create_permutation_with_word(Some(Word::Generator(name)), one_line_notation));
+    }
+
     pub fn create_permutation_from_composition(name: String, permutations: Vec<&Permutation>) -> Self {
         let mut p = compose_n(permutations);
         p.set_name(name);
@@ -161,8 +231,28 @@ impl Permutation {
         return p;
     }
 
-    fn set_name(&mut self, new_name: String) {
-        self.name = new_name;
+    /// Returns the name of the permutation, in puzzle notation when it is a composition, e.g. `f r2 d'`.
+    pub fn name(&self) -> Option<String> {
+        return self.word.as_ref().map(|word| word.to_string());
+    }
+
+    pub fn set_name(&mut self, new_name: String) {
+        self.word = Some(Word::Generator(new_name));
+    }
+
+    pub fn clear_name(&mut self) {
+        self.word = None;
+    }
+
+    /// Returns how the permutation was obtained from named permutations, if known.
+    pub fn word(&self) -> Option<&Word> {
+        return self.word.as_ref();
+    }
+
+    pub fn with_word(mut self, word: Word) -> Self {
+        self.word = Some(word);
+
+        return self;
     }
 
     pub fn degree(&self) -> usize {
@@ -172,7 +262,7 @@ impl Permutation {
 
     pub fn inverse(&self) -> Self {
         if self.is_identity {
-            return Permutation::create_permutation_one_line(self.name.clone(), vec![]);
+            return Permutation::create_permutation_with_word(self.word.as_ref().map(|word| word.inverse()), vec![]);
         }
 
         let mut inv_one_line = vec![0; self.one_line_permutation.len()];
@@ -181,7 +271,7 @@ impl Permutation {
             inv_one_line[self.one_line_permutation[i]] = i;
         }
 
-        return Permutation::create_permutation_one_line(format!("{}i", self.name.clone()), inv_one_line);
+        return Permutation::create_permutation_with_word(self.word.as_ref().map(|word| word.inverse()), inv_one_line);
     }
 
     pub fn apply(&self, i: usize) -> usize {
@@ -198,12 +288,88 @@ impl Permutation {
 
     // Returns conjugation of specified element by this permutation, i.e. self * rhs * self^-1
     pub fn conjugate(&self, rhs: &Self) -> Self {
-        return compose_3(self, rhs, &self.inverse());
+        let word = match (&self.word, &rhs.word) {
+            (Some(a), Some(b)) => Some(a.conjugate(b)),
+            _ => None,
+        };
+
+        return Permutation::create_permutation_with_word(word, compose_3(self, rhs, &self.inverse()).one_line_permutation);
     }
 
     // Returns commutator of this and specified permutation, i.e. rhs * self * rhs^-1 * self^-1
     pub fn commutator(&self, rhs: &Self) -> Self {
-        return compose_4(rhs, self, &rhs.inverse(), &self.inverse());
+        let word = match (&rhs.word, &self.word) {
+            (Some(a), Some(b)) => Some(a.commutator(b)),
+            _ => None,
+        };
+
+        return Permutation::create_permutation_with_word(word, compose_4(rhs, self, &rhs.inverse(), &self.inverse()).one_line_permutation);
+    }
+
+    /// Returns the disjoint cycles of this permutation, fixed points are omitted.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        return convert_one_line_to_cycles(&self.one_line_permutation);
+    }
+
+    /// Returns the lengths of the non trivial cycles, sorted from the longest to the shortest.
+    /// e.g. `[4, 4, 4]` for any face turn of the 2x2.
+    pub fn cycle_type(&self) -> Vec<usize> {
+        let mut cycle_type: Vec<usize> = self.cycles().iter().map(|cycle| cycle.len()).collect();
+        cycle_type.sort_unstable_by(|a, b| b.cmp(a));
+
+        return cycle_type;
+    }
+
+    /// Returns the smallest k > 0 such that self^k is the identity, i.e. the lcm of the cycle lengths.
+    /// It is an arbitrary-precision integer, as it doesn't fit in a u64 for some permutations of a few hundred points.
+    pub fn order(&self) -> BigUint {
+        return self.cycles().iter()
+            .fold(BigUint::from(1u32), |order, cycle| lcm(order, cycle.len() as u64));
+    }
+
+    /// Returns 1 for even permutations and -1 for odd ones.
+    pub fn sign(&self) -> i8 {
+        return if self.is_even() { 1 } else { -1 };
+    }
+
+    /// A permutation is even if it can be written as an even number of transpositions,
+    /// a cycle of length l being the product of l - 1 transpositions.
+    pub fn is_even(&self) -> bool {
+        let transpositions: usize = self.cycles().iter().map(|cycle| cycle.len() - 1).sum();
+
+        return transpositions.is_multiple_of(2);
+    }
+
+    /// Returns the points moved by this permutation, in increasing order.
+    pub fn support(&self) -> Vec<usize> {
+        return (0..self.internal_degree)
+            .filter(|&i| self.one_line_permutation[i] != i)
+            .collect();
+    }
+
+    /// Returns the points below the degree that are not moved by this permutation, in increasing order.
+    /// Every point above the degree is fixed as well.
+    pub fn fixed_points(&self) -> Vec<usize> {
+        return (0..self.internal_degree)
+            .filter(|&i| self.one_line_permutation[i] == i)
+            .collect();
+    }
+
+    /// Returns self^k, negative values of k being powers of the inverse.
+    /// Computed by rotating each cycle by k, so it doesn't depend on the size of k.
+    pub fn pow(&self, k: i64) -> Self {
+        let mut one_line = Vec::from_iter(0..self.internal_degree);
+
+        for cycle in self.cycles() {
+            let length = cycle.len() as i64;
+            let shift = k.rem_euclid(length) as usize;
+
+            for (i, point) in cycle.iter().enumerate() {
+                one_line[*point] = cycle[(i + shift) % cycle.len()];
+            }
+        }
+
+        return Permutation::create_permutation_with_word(self.word.as_ref().map(|word| word.pow(k)), one_line);
     }
 
     pub fn print(&self) -> String {
@@ -218,10 +384,35 @@ impl Permutation {
         return format!("{:?}", &self.one_line_permutation);
     }
 
-    // TODO
-    // pub fn print_two_lines(&self) -> String {
-    //
-    // }
+    /// Returns the mathematical cycle notation, e.g. `(1 4 3 2)(13 42 31 24)`, or `()` for the identity.
+    /// It can be parsed back with `str::parse`.
+    pub fn print_cycle_notation(&self) -> String {
+        if self.is_identity {
+            return "()".to_string();
+        }
+
+        return self.cycles().iter()
+            .map(|cycle| {
+                let points: Vec<String> = cycle.iter().map(|point| point.to_string()).collect();
+                format!("({})", points.join(" "))
+            })
+            .collect();
+    }
+
+    /// Returns the two-line notation restricted to the moved points, each point above its image:
+    /// ```text
+    ///  1  2  3  4 13 14 21 24 31 32 42 43
+    ///  4  1  2  3 42 43 14 13 24 21 31 32
+    /// ```
+    pub fn print_two_lines(&self) -> String {
+        let support = self.support();
+        let width = support.iter().map(|point| point.to_string().len()).max().unwrap_or(0);
+
+        let top: Vec<String> = support.iter().map(|point| format!("{:>width$}", point, width = width)).collect();
+        let bottom: Vec<String> = support.iter().map(|point| format!("{:>width$}", self.apply(*point), width = width)).collect();
+
+        return format!("{}\n{}", top.join(" "), bottom.join(" "));
+    }
 }
 
 
@@ -230,7 +421,12 @@ fn next_false(table: &Vec<bool>, starting_position: usize) -> Option<usize> {
 }
 
 
-fn convert_cycles_to_one_line(cycles: &Vec<Vec<usize>>) -> Vec<usize> {
+fn convert_cycles_to_one_line(cycles: &[Vec<usize>]) -> Vec<usize> {
+    return try_convert_cycles_to_one_line(cycles)
+        .unwrap_or_else(|error| panic!("Illegal use of cycle notation: {}", error));
+}
+
+fn try_convert_cycles_to_one_line(cycles: &[Vec<usize>]) -> Result<Vec<usize>, PermutationError> {
     let mut degree: usize = 0;
     for cycle in cycles {
         if let Some(max) = cycle.iter().max() {
@@ -239,29 +435,55 @@ fn convert_cycles_to_one_line(cycles: &Vec<Vec<usize>>) -> Vec<usize> {
     }
 
     if degree == 0 {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
     degree += 1;
 
     let mut permutation = Vec::from_iter(0..degree);
+    let mut cycle_of_point: Vec<Option<usize>> = vec![None; degree];
+
+    for (c, current_cycle) in cycles.iter().enumerate() {
+        for &point in current_cycle {
+            match cycle_of_point[point] {
+                Some(other) if other == c => {
+                    return Err(PermutationError::RepeatedPointInCycle { point, cycle: current_cycle.clone() });
+                }
+                Some(other) => {
+                    return Err(PermutationError::PointInSeveralCycles { point, first_cycle: cycles[other].clone(), second_cycle: current_cycle.clone() });
+                }
+                None => cycle_of_point[point] = Some(c),
+            }
+        }
 
-    for current_cycle in cycles {
-        if current_cycle.is_empty() { // ID
-            continue;
+        // An empty cycle is the identity and a cycle of length one a fixed point
+        for k in 0..current_cycle.len() {
+            permutation[current_cycle[k]] = current_cycle[(k + 1) % current_cycle.len()];
         }
+    }
+
+    return Ok(permutation);
+}
 
-        if current_cycle.len() == 1 {
-            panic!("Illegal use of cycle notation: {:?}", current_cycle);
+/**
+ * Checks that specified one line notation is a bijection of 0..n, n being its length.
+ */
+fn check_one_line(one_line: &[usize]) -> Result<(), PermutationError> {
+    let mut preimages: Vec<Option<usize>> = vec![None; one_line.len()];
+
+    for (point, &image) in one_line.iter().enumerate() {
+        if image >= one_line.len() {
+            return Err(PermutationError::ImageOutOfRange { point, image });
         }
 
-        for k in 0..(current_cycle.len() - 1) {
-            permutation[current_cycle[k]] = current_cycle[k + 1]
+        if let Some(first_point) = preimages[image] {
+            return Err(PermutationError::RepeatedImage { image, first_point, second_point: point });
         }
-        permutation[current_cycle[current_cycle.len() - 1]] = current_cycle[0];
+
+        preimages[image] = Some(point);
     }
 
-    return permutation;
+    return Ok(());
 }
 
 fn convert_one_line_to_cycles(one_line: &Vec<usize>) -> Vec<Vec<usize>> {
@@ -294,9 +516,28 @@ fn convert_one_line_to_cycles(one_line: &Vec<usize>) -> Vec<Vec<usize>> {
     return cycles;
 }
 
+// Permutations are displayed by name, or in cycle notation when unnamed or with the alternate flag, i.e. `{:#}`.
 impl Display for Permutation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        return write!(f, "{}", self.name);
+        return match &self.word {
+            Some(word) if !f.alternate() => write!(f, "{}", word),
+            _ => write!(f, "{}", self.print_cycle_notation()),
+        };
+    }
+}
+
+// Trailing fixed points are not part of the mapping, so [0, 2, 1] and [0, 2, 1, 3] are the same permutation.
+impl PartialEq for Permutation {
+    fn eq(&self, other: &Self) -> bool {
+        return self.one_line_permutation[..self.internal_degree] == other.one_line_permutation[..other.internal_degree];
+    }
+}
+
+impl Eq for Permutation {}
+
+impl Hash for Permutation {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.one_line_permutation[..self.internal_degree].hash(state);
     }
 }
 
@@ -334,8 +575,38 @@ fn internal_degree(one_line: &Vec<usize>) -> usize {
     return (i + 1) as usize;
 }
 
+fn gcd(a: u64, b: u64) -> u64 {
+    return if b == 0 { a } else { gcd(b, a % b) };
+}
+
+fn lcm(a: BigUint, b: u64) -> BigUint {
+    let remainder = u64::try_from(&a % b).unwrap();
+
+    return a / gcd(b, remainder) * b;
+}
+
+/// Permutations shared by the tests of the modules.
+#[cfg(test)]
+mod test_fixtures {
+    use crate::Permutation;
+
+    /// The six face turns of the 2x2, in the order f, l, u, r, d, b.
+    pub(crate) fn cube_2x2_moves() -> Vec<Permutation> {
+        return vec![
+            Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]),
+            Permutation::create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]]),
+            Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]),
+            Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]),
+            Permutation::create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]]),
+            Permutation::create_permutation("b".to_string(), vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]]),
+        ];
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::{HashMap, HashSet};
+
     use crate::*;
 
     #[test]
@@ -353,8 +624,8 @@ mod test {
         let b_cycles = vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]];
 
         assert_eq!(convert_cycles_to_one_line(&id_cycles), id_one_line);
-        assert_eq!(convert_cycles_to_one_line(&vec![vec![]]), id_one_line);
-        assert_eq!(convert_cycles_to_one_line(&vec![vec![], vec![]]), id_one_line);
+        assert_eq!(convert_cycles_to_one_line(&[vec![]]), id_one_line);
+        assert_eq!(convert_cycles_to_one_line(&[vec![], vec![]]), id_one_line);
 
         assert_eq!(convert_cycles_to_one_line(&f_cycles), f_one_line);
         assert_eq!(convert_cycles_to_one_line(&b_cycles), b_one_line);
@@ -450,7 +721,7 @@ mod test {
         let l: Permutation = Permutation::create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]]);
         let b: Permutation = Permutation::create_permutation("b".to_string(), vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]]);
 
-        assert_eq!(compose(&id, &id), Permutation { name: "id * id".to_string(), one_line_permutation: vec![], is_identity: true, internal_degree: 0 });
+        assert_eq!(compose(&id, &id), Permutation { word: Some(Word::Identity), one_line_permutation: vec![], is_identity: true, internal_degree: 0 });
         assert_eq!(compose(&f, &l),
                    Permutation::create_permutation(
                        "f * l".to_string(),
@@ -543,4 +814,158 @@ mod test {
                    34, 24, 25, 26, 27, 28, 29, 30, 31, 32, 44, 41, 35, 36, 37, 38, 39, 40, 12, 42, 43, \
                    11, 45, 46, 47, 48, 49, 50, 54, 51, 52, 53]".to_string());
     }
+
+    #[test]
+    fn test_cycle_structure() {
+        let id: Permutation = Permutation::identity();
+        let f: Permutation = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+        let r: Permutation = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+        let d: Permutation = Permutation::create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]]);
+
+        let a_tech_right = compose_7(&f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), &d, &r);
+
+        assert_eq!(id.order(), BigUint::from(1u32));
+        assert_eq!(id.cycle_type(), Vec::<usize>::new());
+        assert_eq!(id.sign(), 1);
+        assert!(id.support().is_empty());
+
+        assert_eq!(f.order(), BigUint::from(4u32));
+        assert_eq!(f.cycle_type(), vec![4, 4, 4]);
+        assert_eq!(f.sign(), -1);
+        assert!(!f.is_even());
+        assert_eq!(f.support(), vec![1, 2, 3, 4, 13, 14, 21, 24, 31, 32, 42, 43]);
+        assert_eq!(f.fixed_points().len(), f.degree() - 12);
+        assert!(!f.fixed_points().contains(&1));
+        assert!(f.fixed_points().contains(&11));
+
+        assert_eq!(a_tech_right.order(), BigUint::from(6u32));
+        assert_eq!(a_tech_right.cycle_type(), vec![6, 3]);
+        assert_eq!(a_tech_right.sign(), -1);
+        assert_eq!(compose(&a_tech_right, &f).order(), BigUint::from(15u32));
+
+        let f_d = compose(&f, &d);
+        assert_eq!(f_d.cycle_type(), vec![15, 3]);
+        assert_eq!(f_d.order(), BigUint::from(15u32));
+        assert!(f_d.is_even());
+
+        // Cycles of the 16 first primes, 381 points: the order overflows a u64
+        let primes = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+        let mut start = 0;
+        let mut cycles = Vec::new();
+        for length in primes {
+            cycles.push((start..start + length).collect());
+            start += length;
+        }
+        let big = Permutation::from_cycles(cycles);
+        assert_eq!(big.order(), primes.iter().map(|&p| BigUint::from(p)).product::<BigUint>());
+        assert!(big.order() > BigUint::from(u64::MAX));
+    }
+
+    #[test]
+    fn test_pow() {
+        let f: Permutation = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+        let r: Permutation = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+        let d: Permutation = Permutation::create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]]);
+
+        let a_tech_right = compose_7(&f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), &d, &r);
+
+        assert!(f.pow(0).cycles().is_empty());
+        assert!(f.pow(4).cycles().is_empty());
+        assert!(a_tech_right.pow(6).cycles().is_empty());
+        assert!(a_tech_right.pow(-600).cycles().is_empty());
+
+        assert_eq!(f.pow(1).cycles(), f.cycles());
+        assert_eq!(f.pow(2).cycles(), compose(&f, &f).cycles());
+        assert_eq!(f.pow(-1).cycles(), f.inverse().cycles());
+        assert_eq!(f.pow(5).cycles(), f.cycles());
+        assert_eq!(a_tech_right.pow(2).cycles(), vec![vec![3, 32, 24], vec![23, 33, 54], vec![34, 44, 53]]);
+        assert_eq!(a_tech_right.pow(-2).cycles(), a_tech_right.pow(4).cycles());
+        assert_eq!(a_tech_right.pow(3).cycle_type(), vec![2, 2, 2]);
+        assert_eq!(a_tech_right.pow(1_000_000_007).cycles(), a_tech_right.pow(1_000_000_007 % 6).cycles());
+
+        assert_eq!(f.pow(-1).to_string(), "f'");
+        assert_eq!(f.pow(3).to_string(), "f3");
+    }
+
+    #[test]
+    fn test_equality_ignores_names() {
+        let f: Permutation = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+        let l: Permutation = Permutation::create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]]);
+        let r: Permutation = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+        let d: Permutation = Permutation::create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]]);
+
+        assert_eq!(f, Permutation::from_cycles(vec![vec![13, 42, 31, 24], vec![1, 4, 3, 2], vec![14, 43, 32, 21]]));
+        assert_eq!(Permutation::from_one_line(vec![0, 2, 1]), Permutation::from_one_line(vec![0, 2, 1, 3, 4]));
+        assert_eq!(compose(&f, &f.inverse()), Permutation::identity());
+        assert_eq!(Permutation::from_one_line(vec![0, 1, 2]), Permutation::identity());
+        assert_ne!(f, l);
+
+        let a_tech_right = Permutation::create_permutation_from_composition("a_tech_right".to_string(), vec![&f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), &d, &r]);
+        let same_effect = Permutation::create_permutation("cycles".to_string(), vec![vec![34, 53, 44], vec![3, 23, 32, 33, 24, 54]]);
+        assert_eq!(a_tech_right, same_effect);
+
+        let algorithms: HashSet<Permutation> = HashSet::from([a_tech_right.clone(), same_effect, f.pow(5), f.clone()]);
+        assert_eq!(algorithms.len(), 2);
+        assert!(algorithms.contains(&a_tech_right));
+
+        let mut by_effect: HashMap<Permutation, &str> = HashMap::new();
+        by_effect.insert(compose(&f, &l), "f * l");
+        assert_eq!(by_effect.get(&Permutation::from_cycles(vec![vec![4, 43, 31], vec![1, 11, 53, 24, 13, 42, 41, 44, 32, 21, 14, 52, 34, 3, 2]])), Some(&"f * l"));
+    }
+
+    #[test]
+    fn test_optional_names() {
+        let f: Permutation = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+        let unnamed = Permutation::from_cycles(vec![vec![1, 2]]);
+
+        assert_eq!(f.name(), Some("f".to_string()));
+        assert_eq!(f.inverse().name(), Some("f'".to_string()));
+        assert_eq!(unnamed.name(), None);
+        assert_eq!(unnamed.to_string(), "(1 2)");
+        assert_eq!(compose(&f, &unnamed).name(), None);
+        assert_eq!(compose(&f, &f).name(), Some("f2".to_string()));
+
+        let mut renamed = unnamed.clone();
+        renamed.set_name("swap".to_string());
+        assert_eq!(renamed.to_string(), "swap");
+        assert_eq!(renamed, unnamed);
+
+        renamed.clear_name();
+        assert_eq!(renamed.name(), None);
+    }
+
+    #[test]
+    fn test_try_create_permutation() {
+        let f = Permutation::try_create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+        assert_eq!(f.unwrap().cycle_type(), vec![4, 4, 4]);
+
+        assert_eq!(Permutation::try_from_cycles(vec![vec![1, 2], vec![5], vec![]]).unwrap(), Permutation::from_cycles(vec![vec![1, 2]]));
+
+        assert_eq!(Permutation::try_create_permutation("bad".to_string(), vec![vec![1, 4, 1]]),
+                   Err(PermutationError::RepeatedPointInCycle { point: 1, cycle: vec![1, 4, 1] }));
+        assert_eq!(Permutation::try_from_cycles(vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 3, 21]]),
+                   Err(PermutationError::PointInSeveralCycles { point: 3, first_cycle: vec![1, 4, 3, 2], second_cycle: vec![14, 43, 3, 21] }));
+        assert_eq!(Permutation::try_from_cycles(vec![vec![1, 2], vec![2]]),
+                   Err(PermutationError::PointInSeveralCycles { point: 2, first_cycle: vec![1, 2], second_cycle: vec![2] }));
+    }
+
+    #[test]
+    fn test_try_create_permutation_one_line() {
+        assert_eq!(Permutation::try_from_one_line(vec![0, 2, 1]).unwrap(), Permutation::from_cycles(vec![vec![1, 2]]));
+        assert_eq!(Permutation::try_create_permutation_one_line("id".to_string(), vec![]).unwrap(), Permutation::identity());
+
+        assert_eq!(Permutation::try_from_one_line(vec![0, 3, 1]),
+                   Err(PermutationError::ImageOutOfRange { point: 1, image: 3 }));
+        assert_eq!(Permutation::try_create_permutation_one_line("bad".to_string(), vec![0, 2, 2]),
+                   Err(PermutationError::RepeatedImage { image: 2, first_point: 1, second_point: 2 }));
+
+        assert_eq!(PermutationError::RepeatedImage { image: 2, first_point: 1, second_point: 2 }.to_string(),
+                   "points 1 and 2 are both mapped to 2");
+    }
+
+    #[test]
+    #[should_panic(expected = "point 1 appears more than once in cycle [1, 4, 1]")]
+    fn test_create_invalid_permutation() {
+        Permutation::create_permutation("bad".to_string(), vec![vec![1, 4, 1]]);
+    }
 }
\ No newline at end of file
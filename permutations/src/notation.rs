@@ -0,0 +1,552 @@
+//! Parsing of permutations written as text.
+//!
+//! Both the cycle notation used in papers and by GAP, e.g. `(1 4 3 2)(13 42 31 24)` or `(1,4,3,2)(13,42,31,24)`,
+//! and the one line notation printed by `print_one_line`, e.g. `[0, 4, 1, 2, 3]`, are accepted:
+//! ```
+//! use permutations::Permutation;
+//!
+//! let f: Permutation = "(1 4 3 2)(13 42 31 24)(14 43 32 21)".parse().unwrap();
+//! assert_eq!(f.apply(1), 4);
+//! assert_eq!(f.print_cycle_notation().parse::<Permutation>().unwrap(), f);
+//! ```
+//! Parsed permutations are unnamed, and their points are at most `MAX_PARSED_POINT`.
+//!
+//! Words are written in the puzzle notation printed by `Word`, with powers, inverses, groups, conjugates,
+//! commutators and comments from `//` to the end of the line:
+//! ```
+//! use permutations::notation::{parse_word, ParseWordError, Span};
+//! use permutations::word::Word;
+//!
+//! let word: Word = "R U R' U2 (R U)3 [R, U] [F: R U R'] // sune".parse().unwrap();
+//! assert_eq!(word.to_string(), "R U R' U2 (R U)3 [R, U] [F: R U R']");
+//!
+//! // The move names are mapped, e.g. to the lowercase names of a definition
+//! let known = ["f", "r", "u"];
+//! let resolve = |name: &str| Some(name.to_lowercase()).filter(|name| known.contains(&name.as_str()));
+//! assert_eq!(parse_word("R U'", resolve).unwrap().to_string(), "r u'");
+//! assert_eq!(parse_word("R X2", resolve), Err(ParseWordError::UnknownMove { span: Span { start: 2, end: 3 }, name: "X".to_string() }));
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::PermutationError;
+use crate::Permutation;
+use crate::word::Word;
+
+/// Largest point accepted when parsing, so that a typo can't allocate a permutation of a huge degree.
+pub const MAX_PARSED_POINT: usize = 1 << 24;
+
+#[derive(Debug, PartialEq)]
+pub enum ParsePermutationError {
+    /// A character that has nothing to do in the notation, with its byte position.
+    UnexpectedCharacter { position: usize, character: char },
+    /// A cycle or a one line list is not closed, with the position of its opening bracket.
+    Unclosed { position: usize },
+    /// A number larger than `MAX_PARSED_POINT`, with its position.
+    InvalidNumber { position: usize },
+    /// The text is well formed, but doesn't describe a bijection, e.g. `(1 2)(2 3)`.
+    InvalidPermutation(PermutationError),
+}
+
+impl Display for ParsePermutationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ParsePermutationError::UnexpectedCharacter { position, character } =>
+                write!(f, "unexpected character '{}' at position {}", character, position),
+            ParsePermutationError::Unclosed { position } =>
+                write!(f, "bracket opened at position {} is never closed", position),
+            ParsePermutationError::InvalidNumber { position } =>
+                write!(f, "invalid number at position {}", position),
+            ParsePermutationError::InvalidPermutation(error) =>
+                write!(f, "{}", error),
+        };
+    }
+}
+
+impl std::error::Error for ParsePermutationError {}
+
+impl FromStr for Permutation {
+    type Err = ParsePermutationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokenizer = Tokenizer { text: s, position: 0 };
+
+        tokenizer.skip_whitespaces();
+        if tokenizer.peek() == Some('[') {
+            let one_line = tokenizer.parse_list('[', ']')?;
+            tokenizer.expect_end()?;
+
+            return Permutation::try_from_one_line(one_line).map_err(ParsePermutationError::InvalidPermutation);
+        }
+
+        let mut cycles = Vec::new();
+        while !tokenizer.is_at_end() {
+            cycles.push(tokenizer.parse_list('(', ')')?);
+            tokenizer.skip_whitespaces();
+        }
+
+        return Permutation::try_from_cycles(cycles).map_err(ParsePermutationError::InvalidPermutation);
+    }
+}
+
+struct Tokenizer<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Tokenizer<'_> {
+    fn peek(&self) -> Option<char> {
+        return self.text[self.position..].chars().next();
+    }
+
+    fn is_at_end(&self) -> bool {
+        return self.position >= self.text.len();
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), ParsePermutationError> {
+        self.skip_whitespaces();
+
+        return match self.peek() {
+            None => Ok(()),
+            Some(character) => Err(ParsePermutationError::UnexpectedCharacter { position: self.position, character }),
+        };
+    }
+
+    /// Parses numbers separated by whitespaces or commas between the specified brackets.
+    fn parse_list(&mut self, open: char, close: char) -> Result<Vec<usize>, ParsePermutationError> {
+        let opening_position = self.position;
+
+        match self.peek() {
+            Some(c) if c == open => self.position += 1,
+            Some(character) => return Err(ParsePermutationError::UnexpectedCharacter { position: self.position, character }),
+            None => return Err(ParsePermutationError::Unclosed { position: opening_position }),
+        }
+
+        let mut numbers = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(ParsePermutationError::Unclosed { position: opening_position }),
+                Some(c) if c == close => {
+                    self.position += 1;
+                    return Ok(numbers);
+                }
+                Some(c) if c.is_whitespace() || c == ',' => self.position += 1,
+                Some(c) if c.is_ascii_digit() => numbers.push(self.parse_number()?),
+                Some(character) => return Err(ParsePermutationError::UnexpectedCharacter { position: self.position, character }),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<usize, ParsePermutationError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        return self.text[start..self.position].parse()
+            .ok()
+            .filter(|&point| point <= MAX_PARSED_POINT)
+            .ok_or(ParsePermutationError::InvalidNumber { position: start });
+    }
+}
+
+/// Largest number of moves of a parsed word once its groups, conjugates and commutators are expanded,
+/// the power k of a move counting as k moves, so that expanding it stays cheap.
+pub const MAX_WORD_MOVES: u64 = 1_000_000;
+
+/// Byte range of a part of a text, the end excluded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}..{}", self.start, self.end);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseWordError {
+    /// A character that has nothing to do in the notation.
+    UnexpectedCharacter { span: Span, character: char },
+    /// A group, conjugate or commutator is not closed, spanning from its opening bracket to the end of the text.
+    Unclosed { span: Span },
+    /// A move name that the resolver doesn't know.
+    UnknownMove { span: Span, name: String },
+    /// A repetition count too large, or a part of the word of more than `MAX_WORD_MOVES` moves once expanded.
+    InvalidNumber { span: Span },
+}
+
+impl ParseWordError {
+    /// Returns the part of the text where the error is.
+    pub fn span(&self) -> Span {
+        return match self {
+            ParseWordError::UnexpectedCharacter { span, .. } => *span,
+            ParseWordError::Unclosed { span } => *span,
+            ParseWordError::UnknownMove { span, .. } => *span,
+            ParseWordError::InvalidNumber { span } => *span,
+        };
+    }
+
+    /// Returns the line of the text where the error is, underlined, to be printed below the error.
+    pub fn underline(&self, text: &str) -> String {
+        let span = self.span();
+        let line_start = text[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[span.start..].find('\n').map_or(text.len(), |i| span.start + i);
+        let length = text[span.start..span.end.min(line_end)].chars().count().max(1);
+
+        return format!("{}\n{}{}", &text[line_start..line_end], " ".repeat(text[line_start..span.start].chars().count()), "^".repeat(length));
+    }
+}
+
+impl Display for ParseWordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ParseWordError::UnexpectedCharacter { span, character } =>
+                write!(f, "unexpected character '{}' at {}", character, span),
+            ParseWordError::Unclosed { span } =>
+                write!(f, "bracket opened at {} is never closed", span.start),
+            ParseWordError::UnknownMove { span, name } =>
+                write!(f, "unknown move {} at {}", name, span),
+            ParseWordError::InvalidNumber { span } =>
+                write!(f, "invalid number at {}", span),
+        };
+    }
+}
+
+impl std::error::Error for ParseWordError {}
+
+/// Parses a word, keeping the move names as they are written.
+impl FromStr for Word {
+    type Err = ParseWordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return parse_word(s, |name| Some(name.to_string()));
+    }
+}
+
+/// Parses a word in the puzzle notation, mapping every move name with resolve, which returns None for unknown moves.
+/// A move name is made of letters and underscores, the digits after it being a repetition count, and `id` is the identity.
+pub fn parse_word<F>(text: &str, resolve: F) -> Result<Word, ParseWordError>
+    where F: Fn(&str) -> Option<String> {
+    let mut parser = WordParser { tokenizer: Tokenizer { text, position: 0 }, resolve };
+
+    let (word, _) = parser.parse_sequence()?;
+
+    return match parser.peek() {
+        None => Ok(word),
+        Some(character) => Err(parser.unexpected(character)),
+    };
+}
+
+struct WordParser<'a, F> {
+    tokenizer: Tokenizer<'a>,
+    resolve: F,
+}
+
+impl<F> WordParser<'_, F>
+    where F: Fn(&str) -> Option<String> {
+    /// Returns the next character that is neither a whitespace nor in a comment.
+    fn peek(&mut self) -> Option<char> {
+        loop {
+            self.tokenizer.skip_whitespaces();
+
+            if !self.tokenizer.text[self.tokenizer.position..].starts_with("//") {
+                return self.tokenizer.peek();
+            }
+
+            let rest = &self.tokenizer.text[self.tokenizer.position..];
+            self.tokenizer.position += rest.find('\n').unwrap_or(rest.len());
+        }
+    }
+
+    fn unexpected(&self, character: char) -> ParseWordError {
+        let start = self.tokenizer.position;
+
+        return ParseWordError::UnexpectedCharacter { span: Span { start, end: start + character.len_utf8() }, character };
+    }
+
+    /// Parses the terms up to the end of the text or to a closing bracket or separator, left for the caller.
+    /// Returns the word with its number of moves once expanded.
+    fn parse_sequence(&mut self) -> Result<(Word, u64), ParseWordError> {
+        let mut terms = Vec::new();
+        let mut move_count: u64 = 0;
+
+        while let Some(c) = self.peek() {
+            if matches!(c, ')' | ']' | ',' | ':') {
+                break;
+            }
+
+            let start = self.tokenizer.position;
+            let (term, term_move_count) = self.parse_term()?;
+            move_count = self.checked_move_count(move_count.checked_add(term_move_count), start)?;
+            terms.push(term);
+        }
+
+        let word = match terms.len() {
+            0 => Word::Identity,
+            1 => terms.pop().unwrap(),
+            _ => Word::Sequence(terms),
+        };
+
+        return Ok((word, move_count));
+    }
+
+    /// Parses a move, a group in parentheses, a conjugate or a commutator, followed by its count and prime.
+    /// Returns the word with its number of moves once expanded.
+    fn parse_term(&mut self) -> Result<(Word, u64), ParseWordError> {
+        let start = self.tokenizer.position;
+
+        let (word, move_count) = match self.peek() {
+            Some('(') => {
+                self.tokenizer.position += 1;
+                let group = self.parse_sequence()?;
+                self.expect(')', start)?;
+
+                group
+            }
+            Some('[') => {
+                self.tokenizer.position += 1;
+                let a = self.parse_sequence()?;
+                let separator = self.peek();
+                if !matches!(separator, Some(',') | Some(':')) {
+                    return Err(self.unclosed_or_unexpected(separator, start));
+                }
+
+                self.tokenizer.position += 1;
+                let (b, b_move_count) = self.parse_sequence()?;
+                self.expect(']', start)?;
+
+                let (a, a_move_count) = a;
+                match separator {
+                    Some(',') => (a.commutator(&b), self.checked_move_count(a_move_count.checked_add(b_move_count).and_then(|n| n.checked_mul(2)), start)?),
+                    _ => (a.conjugate(&b), self.checked_move_count(a_move_count.checked_mul(2).and_then(|n| n.checked_add(b_move_count)), start)?),
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => self.parse_move()?,
+            Some(character) => return Err(self.unexpected(character)),
+            None => return Err(ParseWordError::Unclosed { span: Span { start, end: self.tokenizer.text.len() } }),
+        };
+
+        // The count and the prime come in either order: R2' or R'2
+        let mut count = self.parse_count()?;
+        let inverse = self.tokenizer.peek() == Some('\'');
+        if inverse {
+            self.tokenizer.position += 1;
+            let count_start = self.tokenizer.position;
+            match (count, self.parse_count()?) {
+                (None, after) => count = after,
+                (Some(_), Some(_)) =>
+                    return Err(ParseWordError::InvalidNumber { span: Span { start: count_start, end: self.tokenizer.position } }),
+                (Some(_), None) => {}
+            }
+        }
+
+        let count = count.unwrap_or(1);
+        let move_count = self.checked_move_count(move_count.checked_mul(count.unsigned_abs()), start)?;
+        let word = word.pow(count);
+
+        return Ok((if inverse { word.inverse() } else { word }, move_count));
+    }
+
+    /// Returns the number of moves, or an error spanning from start to the current position if it is above `MAX_WORD_MOVES`.
+    fn checked_move_count(&self, move_count: Option<u64>, start: usize) -> Result<u64, ParseWordError> {
+        return move_count
+            .filter(|&move_count| move_count <= MAX_WORD_MOVES)
+            .ok_or(ParseWordError::InvalidNumber { span: Span { start, end: self.tokenizer.position } });
+    }
+
+    fn parse_move(&mut self) -> Result<(Word, u64), ParseWordError> {
+        let start = self.tokenizer.position;
+        while self.tokenizer.peek().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') {
+            self.tokenizer.position += 1;
+        }
+
+        let span = Span { start, end: self.tokenizer.position };
+        let name = &self.tokenizer.text[span.start..span.end];
+        if name == "id" {
+            return Ok((Word::Identity, 0));
+        }
+
+        return match (self.resolve)(name) {
+            Some(resolved) => Ok((Word::Generator(resolved), 1)),
+            None => Err(ParseWordError::UnknownMove { span, name: name.to_string() }),
+        };
+    }
+
+    fn parse_count(&mut self) -> Result<Option<i64>, ParseWordError> {
+        let start = self.tokenizer.position;
+        while self.tokenizer.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.tokenizer.position += 1;
+        }
+
+        if start == self.tokenizer.position {
+            return Ok(None);
+        }
+
+        let span = Span { start, end: self.tokenizer.position };
+
+        return self.tokenizer.text[span.start..span.end].parse().map(Some)
+            .map_err(|_| ParseWordError::InvalidNumber { span });
+    }
+
+    fn expect(&mut self, close: char, opening_position: usize) -> Result<(), ParseWordError> {
+        return match self.peek() {
+            Some(c) if c == close => {
+                self.tokenizer.position += 1;
+                Ok(())
+            }
+            other => Err(self.unclosed_or_unexpected(other, opening_position)),
+        };
+    }
+
+    fn unclosed_or_unexpected(&self, character: Option<char>, opening_position: usize) -> ParseWordError {
+        return match character {
+            Some(character) => self.unexpected(character),
+            None => ParseWordError::Unclosed { span: Span { start: opening_position, end: self.tokenizer.text.len() } },
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::error::PermutationError;
+    use crate::notation::{parse_word, ParsePermutationError, ParseWordError, Span};
+    use crate::word::Word;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_parse_cycle_notation() {
+        let f = &cube_2x2_moves()[0];
+
+        assert_eq!("(1 4 3 2)(13 42 31 24)(14 43 32 21)".parse::<Permutation>().unwrap(), *f);
+        assert_eq!("(1,4,3,2)(13,42,31,24)(14,43,32,21)".parse::<Permutation>().unwrap(), *f);
+        assert_eq!(" (1, 4, 3, 2)\n (13, 42, 31, 24) (14, 43, 32, 21) ".parse::<Permutation>().unwrap(), *f);
+        assert_eq!("(1 4 3 2)(5)(13 42 31 24)(14 43 32 21)".parse::<Permutation>().unwrap(), *f);
+
+        assert_eq!("()".parse::<Permutation>().unwrap(), Permutation::identity());
+        assert_eq!("".parse::<Permutation>().unwrap(), Permutation::identity());
+        assert_eq!("(1 4 3 2)".parse::<Permutation>().unwrap().name(), None);
+    }
+
+    #[test]
+    fn test_parse_one_line() {
+        let f = &cube_2x2_moves()[0];
+
+        assert_eq!(f.print_one_line().parse::<Permutation>().unwrap(), *f);
+        assert_eq!("[0 2 1]".parse::<Permutation>().unwrap(), "(1 2)".parse::<Permutation>().unwrap());
+        assert_eq!("[]".parse::<Permutation>().unwrap(), Permutation::identity());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("(1 2".parse::<Permutation>(), Err(ParsePermutationError::Unclosed { position: 0 }));
+        assert_eq!("(1 2)(3 4".parse::<Permutation>(), Err(ParsePermutationError::Unclosed { position: 5 }));
+        assert_eq!("(1 a)".parse::<Permutation>(), Err(ParsePermutationError::UnexpectedCharacter { position: 3, character: 'a' }));
+        assert_eq!("(1 2) 3".parse::<Permutation>(), Err(ParsePermutationError::UnexpectedCharacter { position: 6, character: '3' }));
+        assert_eq!("[0 1] (1 2)".parse::<Permutation>(), Err(ParsePermutationError::UnexpectedCharacter { position: 6, character: '(' }));
+        assert_eq!("(1 99999999999999999999999)".parse::<Permutation>(), Err(ParsePermutationError::InvalidNumber { position: 3 }));
+        assert_eq!("(0 18446744073709551615)".parse::<Permutation>(), Err(ParsePermutationError::InvalidNumber { position: 3 }));
+        assert_eq!("(1 16777217)".parse::<Permutation>(), Err(ParsePermutationError::InvalidNumber { position: 3 }));
+        assert_eq!("[0 16777217]".parse::<Permutation>(), Err(ParsePermutationError::InvalidNumber { position: 3 }));
+
+        assert_eq!("(1 2)(2 3)".parse::<Permutation>(),
+                   Err(ParsePermutationError::InvalidPermutation(PermutationError::PointInSeveralCycles { point: 2, first_cycle: vec![1, 2], second_cycle: vec![2, 3] })));
+        assert_eq!("[0 1 1]".parse::<Permutation>(),
+                   Err(ParsePermutationError::InvalidPermutation(PermutationError::RepeatedImage { image: 1, first_point: 1, second_point: 2 })));
+
+        assert_eq!(ParsePermutationError::Unclosed { position: 5 }.to_string(), "bracket opened at position 5 is never closed");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let moves = cube_2x2_moves();
+        let (f, l, r, d) = (&moves[0], &moves[1], &moves[3], &moves[4]);
+
+        let a_tech_right = compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r);
+        let samples = [Permutation::identity(), compose(f, l), a_tech_right, f.commutator(r), compose_n(moves.iter().collect())];
+
+        for permutation in moves.iter().chain(samples.iter()) {
+            assert_eq!(permutation.print_cycle_notation().parse::<Permutation>().unwrap(), *permutation);
+            assert_eq!(format!("{:#}", permutation).parse::<Permutation>().unwrap(), *permutation);
+            assert_eq!(permutation.print_one_line().parse::<Permutation>().unwrap(), *permutation);
+        }
+    }
+
+    #[test]
+    fn test_print() {
+        let moves = cube_2x2_moves();
+        let f = &moves[0];
+
+        assert_eq!(f.print_cycle_notation(), "(1 4 3 2)(13 42 31 24)(14 43 32 21)");
+        assert_eq!(format!("{:#}", f), "(1 4 3 2)(13 42 31 24)(14 43 32 21)");
+        assert_eq!(f.to_string(), "f");
+        assert_eq!(Permutation::identity().print_cycle_notation(), "()");
+
+        assert_eq!(f.print_two_lines(),
+                   " 1  2  3  4 13 14 21 24 31 32 42 43\n \
+                     4  1  2  3 42 43 14 13 24 21 31 32");
+        assert_eq!("(2 10)".parse::<Permutation>().unwrap().print_two_lines(),
+                   " 2 10\n10  2");
+        assert_eq!(Permutation::identity().print_two_lines(), "\n");
+    }
+
+    #[test]
+    fn test_parse_word() {
+        let word: Word = "f r' u2 (f r)3 [f: r u r'] [r, u]".parse().unwrap();
+        assert_eq!(word.to_string(), "f r' u2 (f r)3 [f: r u r'] [r, u]");
+        assert_eq!(word.to_string().parse::<Word>().unwrap(), word);
+
+        assert_eq!("r'2".parse::<Word>().unwrap(), "r2'".parse::<Word>().unwrap());
+        assert_eq!("r'2 f".parse::<Word>().unwrap().to_string(), "r2' f");
+        assert_eq!("(f r)0 id".parse::<Word>().unwrap(), Word::Sequence(vec![Word::Identity, Word::Identity]));
+        assert_eq!("  // nothing\n".parse::<Word>().unwrap(), Word::Identity);
+        assert_eq!("[f [r, u]: d]".parse::<Word>().unwrap().to_string(), "[f [r, u]: d]");
+
+        // The move names of the 2x2 are lowercase
+        let moves = cube_2x2_moves();
+        let resolve = |name: &str| moves.iter().map(|g| g.name().unwrap()).find(|g| *g == name.to_lowercase());
+        let sexy_move = parse_word("R U R' U' // sexy move\n", resolve).unwrap();
+        let lookup = |name: &str| moves.iter().find(|g| g.name().as_deref() == Some(name));
+        assert_eq!(sexy_move.evaluate(&lookup).unwrap(), parse_word("[R, U]", resolve).unwrap().evaluate(&lookup).unwrap());
+        assert_eq!(parse_word("(R U)6", resolve).unwrap().evaluate(&lookup).unwrap().order(), BigUint::from(5u32));
+    }
+
+    #[test]
+    fn test_parse_word_errors() {
+        let parse = |text: &str| text.parse::<Word>();
+
+        assert_eq!(parse("f (r u"), Err(ParseWordError::Unclosed { span: Span { start: 2, end: 6 } }));
+        assert_eq!(parse("[f r]"), Err(ParseWordError::UnexpectedCharacter { span: Span { start: 4, end: 5 }, character: ']' }));
+        assert_eq!(parse("f) r"), Err(ParseWordError::UnexpectedCharacter { span: Span { start: 1, end: 2 }, character: ')' }));
+        assert_eq!(parse("f 2"), Err(ParseWordError::UnexpectedCharacter { span: Span { start: 2, end: 3 }, character: '2' }));
+        assert_eq!(parse("f99999999999999999999"), Err(ParseWordError::InvalidNumber { span: Span { start: 1, end: 21 } }));
+        assert_eq!(parse("(f2)5000000000000000000"), Err(ParseWordError::InvalidNumber { span: Span { start: 0, end: 23 } }));
+        assert_eq!(parse("f (r u)1000000000000"), Err(ParseWordError::InvalidNumber { span: Span { start: 2, end: 20 } }));
+        assert_eq!(parse("f [r u, d]500000"), Err(ParseWordError::InvalidNumber { span: Span { start: 2, end: 16 } }));
+        assert_eq!(parse("((f r)1000)1000"), Err(ParseWordError::InvalidNumber { span: Span { start: 0, end: 15 } }));
+        assert_eq!(parse("f r2'3"), Err(ParseWordError::InvalidNumber { span: Span { start: 5, end: 6 } }));
+        assert_eq!(parse("r2'33 f"), Err(ParseWordError::InvalidNumber { span: Span { start: 3, end: 5 } }));
+        assert_eq!(parse("(f r)500000").unwrap().move_count(), 1_000_000);
+
+        let text = "R U\nR' X2 U'";
+        let error = parse_word(text, |name| Some(name.to_string()).filter(|name| name != "X")).unwrap_err();
+        assert_eq!(error, ParseWordError::UnknownMove { span: Span { start: 7, end: 8 }, name: "X".to_string() });
+        assert_eq!(error.to_string(), "unknown move X at 7..8");
+        assert_eq!(error.underline(text), "R' X2 U'\n   ^");
+    }
+}
@@ -0,0 +1,190 @@
+//! Operators on permutations.
+//!
+//! `a * b` is a then b, like `compose(a, b)`, i.e. the mathematical `a(b(x))`, and `-a` is the inverse of a.
+//! Both work on references and on owned permutations, so that algorithms read like their notation:
+//! ```
+//! use permutations::{perm, Permutation};
+//!
+//! let f = perm!("f" => (1 4 3 2)(13 42 31 24)(14 43 32 21));
+//! let r = perm!("r" => (2 32 54 12)(3 33 51 13)(21 24 23 22));
+//! let d = perm!("d" => (3 43 53 23)(4 44 54 24)(31 34 33 32));
+//!
+//! let a_tech_right = &f * -&d * -&f * -&d * -&r * &d * &r;
+//! assert_eq!(a_tech_right.name().unwrap(), "f d' f' d' r' d r");
+//!
+//! let moves = [f, r, d];
+//! let product: Permutation = moves.iter().product();
+//! assert_eq!(product, &moves[0] * &moves[1] * &moves[2]);
+//! ```
+
+use std::iter::Product;
+use std::ops::{Mul, MulAssign, Neg};
+
+use crate::{compose, Permutation};
+
+impl Mul<&Permutation> for &Permutation {
+    type Output = Permutation;
+
+    fn mul(self, rhs: &Permutation) -> Permutation {
+        return compose(self, rhs);
+    }
+}
+
+impl Mul<Permutation> for &Permutation {
+    type Output = Permutation;
+
+    fn mul(self, rhs: Permutation) -> Permutation {
+        return compose(self, &rhs);
+    }
+}
+
+impl Mul<&Permutation> for Permutation {
+    type Output = Permutation;
+
+    fn mul(self, rhs: &Permutation) -> Permutation {
+        return compose(&self, rhs);
+    }
+}
+
+impl Mul<Permutation> for Permutation {
+    type Output = Permutation;
+
+    fn mul(self, rhs: Permutation) -> Permutation {
+        return compose(&self, &rhs);
+    }
+}
+
+impl MulAssign<&Permutation> for Permutation {
+    fn mul_assign(&mut self, rhs: &Permutation) {
+        *self = compose(self, rhs);
+    }
+}
+
+impl Neg for &Permutation {
+    type Output = Permutation;
+
+    fn neg(self) -> Permutation {
+        return self.inverse();
+    }
+}
+
+impl Neg for Permutation {
+    type Output = Permutation;
+
+    fn neg(self) -> Permutation {
+        return self.inverse();
+    }
+}
+
+/// The product of the permutations in iteration order, the identity for an empty iterator.
+impl<'a> Product<&'a Permutation> for Permutation {
+    fn product<I: Iterator<Item=&'a Permutation>>(iter: I) -> Self {
+        return iter.fold(Permutation::identity(), |product, permutation| product * permutation);
+    }
+}
+
+impl Product<Permutation> for Permutation {
+    fn product<I: Iterator<Item=Permutation>>(iter: I) -> Self {
+        return iter.fold(Permutation::identity(), |product, permutation| product * permutation);
+    }
+}
+
+/// Creates a permutation from cycle literals, separated by spaces or commas.
+/// The permutation is named when the cycles are preceded by `"name" =>`.
+/// ```
+/// use permutations::{perm, BigUint};
+///
+/// let f = perm!("f" => (1 4 3 2)(13 42 31 24)(14 43 32 21));
+/// assert_eq!(f, perm!((1, 4, 3, 2)(13, 42, 31, 24)(14, 43, 32, 21)));
+/// assert_eq!(perm!().order(), BigUint::from(1u32));
+/// ```
+/// Panics if the cycles are not disjoint.
+#[macro_export]
+macro_rules! perm {
+    ($( ( $($point:literal $(,)?)* ) )*) => {
+        $crate::Permutation::from_cycles(vec![$(vec![$($point),*]),*])
+    };
+    ($name:literal => $( ( $($point:literal $(,)?)* ) )*) => {
+        $crate::Permutation::create_permutation($name.to_string(), vec![$(vec![$($point),*]),*])
+    };
+}
+
+/// Composes the permutations from left to right, `compose!(a, b, c)` being `&a * &b * &c`.
+/// Both permutations and references to permutations are accepted.
+/// ```
+/// use permutations::{compose, perm, Permutation};
+///
+/// let f = perm!("f" => (1 4 3 2)(13 42 31 24)(14 43 32 21));
+/// let r = perm!("r" => (2 32 54 12)(3 33 51 13)(21 24 23 22));
+/// assert_eq!(compose!(f, r, f.inverse()), f.conjugate(&r));
+/// assert_eq!(compose!(), Permutation::identity());
+/// ```
+#[macro_export]
+macro_rules! compose {
+    () => {
+        $crate::Permutation::identity()
+    };
+    ($($permutation:expr),* $(,)?) => {
+        [$(::std::borrow::Borrow::<$crate::Permutation>::borrow(&$permutation)),*].into_iter().product::<$crate::Permutation>()
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_perm_macro() {
+        let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+
+        let named = perm!("f" => (1 4 3 2)(13 42 31 24)(14 43 32 21));
+        assert_eq!(named, f);
+        assert_eq!(named.name().unwrap(), "f");
+        assert_eq!(perm!("b" => (11 22 33 44)(12 23 34 41)(51 54 53 52)), cube_2x2_moves()[5]);
+        assert_eq!(perm!((1, 4, 3, 2)(13, 42, 31, 24)(14, 43, 32, 21)), f);
+        assert_eq!(perm!((1, 4, 3, 2)(13, 42, 31, 24)(14, 43, 32, 21)).name(), None);
+        assert_eq!(perm!(), Permutation::identity());
+        assert_eq!(perm!("id" =>).name().unwrap(), "id");
+    }
+
+    #[test]
+    fn test_operators() {
+        let moves = cube_2x2_moves();
+        let (f, r, d) = (&moves[0], &moves[3], &moves[4]);
+
+        assert_eq!(f * r, compose(f, r));
+        assert_eq!(f.clone() * r, compose(f, r));
+        assert_eq!(f * r.clone(), compose(f, r));
+        assert_eq!(f.clone() * r.clone(), compose(f, r));
+        assert_eq!(-f, f.inverse());
+        assert_eq!(-f.clone(), f.inverse());
+        assert!((f * -f).is_identity);
+
+        let a_tech_right = f * -d * -f * -d * -r * d * r;
+        assert_eq!(a_tech_right, compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r));
+        assert_eq!(a_tech_right.name().unwrap(), "f d' f' d' r' d r");
+
+        let mut product = Permutation::identity();
+        product *= f;
+        product *= r;
+        assert_eq!(product, f * r);
+        assert_eq!(product.name().unwrap(), "f r");
+    }
+
+    #[test]
+    fn test_product() {
+        let moves = cube_2x2_moves();
+        let (f, l, b) = (&moves[0], &moves[1], &moves[5]);
+
+        let sequence = [f, b, l, f, b, l, f];
+        assert_eq!(sequence.into_iter().product::<Permutation>(), compose_n(sequence.to_vec()));
+        assert_eq!(moves.iter().product::<Permutation>(), compose_n(moves.iter().collect()));
+        assert_eq!(moves.iter().map(|m| m.pow(2)).product::<Permutation>().name().unwrap(), "f2 l2 u2 r2 d2 b2");
+        assert_eq!(Vec::<Permutation>::new().into_iter().product::<Permutation>(), Permutation::identity());
+
+        assert_eq!(compose!(f, b, l, f, b, l, f), compose_n(sequence.to_vec()));
+        assert_eq!(compose!(f.pow(2), -l), compose(&f.pow(2), &l.inverse()));
+        assert_eq!(compose!(), Permutation::identity());
+    }
+}
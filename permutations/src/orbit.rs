@@ -0,0 +1,241 @@
+//! Orbits of points under a set of generators.
+//!
+//! The orbit of a point is the set of positions it can be sent to by the moves, e.g. on a puzzle the facelets
+//! of a corner can only go to corner positions. The orbits partition the points:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::orbit::orbits;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let orbits = orbits(vec![&f]);
+//! assert!(orbits.contains(&vec![1, 2, 3, 4]));
+//! assert!(orbits.contains(&vec![0]));
+//! ```
+//! A Schreier vector stores an orbit together with the way each point is reached,
+//! from which the stabilizer of a point is generated.
+
+use crate::group::PermutationGroup;
+use crate::Permutation;
+
+/// Returns the orbit of the point under the group generated by the specified permutations,
+/// in breadth first order, starting with the point itself.
+pub fn orbit(point: usize, generators: Vec<&Permutation>) -> Vec<usize> {
+    return SchreierVector::create_schreier_vector(point, generators).orbit().clone();
+}
+
+/// Returns the partition of the points below the degree of the generators into orbits.
+/// Every orbit is sorted, and the orbits are sorted by smallest point; fixed points are orbits of length one.
+pub fn orbits(generators: Vec<&Permutation>) -> Vec<Vec<usize>> {
+    let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0);
+
+    let mut orbits = Vec::new();
+    let mut visited = vec![false; degree];
+
+    for point in 0..degree {
+        if visited[point] {
+            continue;
+        }
+
+        let mut orbit = orbit(point, generators.clone());
+        for &x in &orbit {
+            visited[x] = true;
+        }
+
+        orbit.sort_unstable();
+        orbits.push(orbit);
+    }
+
+    return orbits;
+}
+
+/// Orbit of a root point, where every point but the root remembers the generator it was first reached with.
+/// Following these generators back to the root gives an element mapping the root to the point.
+pub struct SchreierVector {
+    root: usize,
+    generators: Vec<Permutation>,
+    orbit: Vec<usize>,
+
+    // vector[y] = Some(k) if y = generators[k](x) for a point x found before y
+    vector: Vec<Option<usize>>,
+}
+
+impl SchreierVector {
+    pub fn create_schreier_vector(root: usize, generators: Vec<&Permutation>) -> Self {
+        let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0).max(root + 1);
+
+        let mut orbit = vec![root];
+        let mut vector = vec![None; degree];
+        let mut visited = vec![false; degree];
+        visited[root] = true;
+
+        let mut i = 0;
+        while i < orbit.len() {
+            let x = orbit[i];
+
+            for (k, generator) in generators.iter().enumerate() {
+                let y = generator.apply(x);
+
+                if !visited[y] {
+                    visited[y] = true;
+                    vector[y] = Some(k);
+                    orbit.push(y);
+                }
+            }
+
+            i += 1;
+        }
+
+        return Self {
+            root,
+            generators: generators.iter().map(|&g| g.clone()).collect(),
+            orbit,
+            vector,
+        };
+    }
+
+    pub fn root(&self) -> usize {
+        return self.root;
+    }
+
+    /// Points of the orbit, in the order they were found.
+    pub fn orbit(&self) -> &Vec<usize> {
+        return &self.orbit;
+    }
+
+    pub fn contains(&self, point: usize) -> bool {
+        return point == self.root || self.vector.get(point).is_some_and(|k| k.is_some());
+    }
+
+    /// Returns the raw vector: for every point of the orbit but the root, the index of the generator it was reached with.
+    pub fn vector(&self) -> &Vec<Option<usize>> {
+        return &self.vector;
+    }
+
+    /// Returns the indices of the generators (k1, ..., kn) such that the composition `g_k1 * ... * g_kn`
+    /// maps the root to the point, or None if the point is not in the orbit.
+    pub fn trace(&self, point: usize) -> Option<Vec<usize>> {
+        if !self.contains(point) {
+            return None;
+        }
+
+        let mut trace = Vec::new();
+        let mut current = point;
+        while let Some(k) = self.vector[current] {
+            trace.push(k);
+            current = self.generators[k].inverse().apply(current);
+        }
+
+        return Some(trace);
+    }
+
+    /// Returns an element of the group mapping the root to the point, named after the generators,
+    /// or None if the point is not in the orbit.
+    pub fn transversal_element(&self, point: usize) -> Option<Permutation> {
+        return Some(self.trace(point)?.iter().map(|&k| &self.generators[k]).product());
+    }
+}
+
+impl PermutationGroup {
+    /// Returns the orbit of the point under the group, in breadth first order.
+    pub fn orbit(&self, point: usize) -> Vec<usize> {
+        return orbit(point, self.generators().iter().collect());
+    }
+
+    /// Returns the partition of the points below the degree of the group into orbits, see `orbits`.
+    pub fn orbits(&self) -> Vec<Vec<usize>> {
+        return orbits(self.generators().iter().collect());
+    }
+
+    /// Returns the subgroup of the elements fixing the point.
+    /// It is generated by the Schreier generators `u_gx^-1 * g * u_x`, u_x mapping the point to x,
+    /// named after the generators of this group.
+    pub fn stabilizer(&self, point: usize) -> PermutationGroup {
+        let schreier_vector = SchreierVector::create_schreier_vector(point, self.generators().iter().collect());
+
+        let mut schreier_generators: Vec<Permutation> = Vec::new();
+        for &x in schreier_vector.orbit() {
+            let u_x = schreier_vector.transversal_element(x).unwrap();
+
+            for generator in self.generators() {
+                let u_gx = schreier_vector.transversal_element(generator.apply(x)).unwrap();
+                let schreier_generator = -&u_gx * generator * &u_x;
+
+                if !schreier_generator.is_identity && !schreier_generators.contains(&schreier_generator) {
+                    schreier_generators.push(schreier_generator);
+                }
+            }
+        }
+
+        return PermutationGroup::create_group(schreier_generators.iter().collect());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::group::PermutationGroup;
+    use crate::orbit::{orbit, orbits, SchreierVector};
+    use crate::test_fixtures::cube_2x2_moves;
+
+    fn facelets() -> Vec<usize> {
+        return [1, 11, 21, 31, 41, 51].iter().flat_map(|&first| first..first + 4).collect();
+    }
+
+    #[test]
+    fn test_orbits() {
+        let moves = cube_2x2_moves();
+
+        let orbits = orbits(moves.iter().collect());
+        assert!(orbits.contains(&facelets()));
+        assert_eq!(orbits.len(), 55 - 24 + 1);
+        assert!(orbits.iter().filter(|orbit| orbit.len() == 1).all(|orbit| !facelets().contains(&orbit[0])));
+
+        // Turning only f, each facelet stays in its cycle
+        let f_orbits: Vec<Vec<usize>> = super::orbits(vec![&moves[0]]).into_iter().filter(|orbit| orbit.len() > 1).collect();
+        assert_eq!(f_orbits, vec![vec![1, 2, 3, 4], vec![13, 24, 31, 42], vec![14, 21, 32, 43]]);
+
+        assert_eq!(orbit(1, vec![&moves[0]]), vec![1, 4, 3, 2]);
+        assert_eq!(orbit(5, moves.iter().collect()), vec![5]);
+        assert_eq!(super::orbits(vec![]), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn test_schreier_vector() {
+        let moves = cube_2x2_moves();
+        let schreier_vector = SchreierVector::create_schreier_vector(1, moves.iter().collect());
+
+        assert_eq!(schreier_vector.root(), 1);
+        assert_eq!(schreier_vector.orbit().len(), 24);
+        assert!(schreier_vector.contains(1));
+        assert!(!schreier_vector.contains(5));
+        assert_eq!(schreier_vector.trace(1), Some(vec![]));
+        assert_eq!(schreier_vector.trace(5), None);
+        assert_eq!(schreier_vector.vector()[4], Some(0));
+
+        for &point in schreier_vector.orbit() {
+            let u = schreier_vector.transversal_element(point).unwrap();
+            assert_eq!(u.apply(1), point);
+            let letters: u64 = u.word().unwrap().letters().iter().map(|(_, k)| k.unsigned_abs()).sum();
+            assert_eq!(letters as usize, schreier_vector.trace(point).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn test_stabilizer() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+
+        let stabilizer = group.stabilizer(1);
+        assert_eq!(stabilizer.order() * 24u32, group.order());
+        assert!(stabilizer.generators().iter().all(|g| g.apply(1) == 1 && group.contains(g)));
+        assert!(stabilizer.contains(&moves[5]));
+        assert!(!stabilizer.contains(&moves[0]));
+
+        // Fixing a facelet of the dlb corner fixes the whole corner: the usual 2x2 group
+        assert_eq!(group.stabilizer(53).order(), BigUint::from(3_674_160u32));
+
+        assert_eq!(group.stabilizer(5).order(), group.order());
+        assert_eq!(group.orbit(1).len(), 24);
+        assert_eq!(group.orbits(), orbits(moves.iter().collect()));
+    }
+}
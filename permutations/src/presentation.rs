@@ -0,0 +1,465 @@
+//! Groups given by a presentation: named generators and relators, words equal to the identity.
+//!
+//! A relation among moves, e.g. `(f r)15`, can be checked on the permutations of the moves.
+//! The Todd-Coxeter algorithm enumerates the cosets of a subgroup of the group of the presentation,
+//! and the action of the generators on the cosets gives back permutations:
+//! ```
+//! use permutations::BigUint;
+//! use permutations::presentation::Presentation;
+//! use permutations::word::Word;
+//!
+//! let (a, b) = (Word::generator("a"), Word::generator("b"));
+//! let s3 = Presentation::create_presentation(vec!["a", "b"], vec![a.pow(2), b.pow(3), a.then(&b).pow(2)]);
+//!
+//! let cosets = s3.enumerate_cosets(vec![], 1_000).unwrap();
+//! assert_eq!(cosets.index(), 6);
+//! assert_eq!(cosets.group().order(), BigUint::from(6u32));
+//!
+//! // The 3 cosets of the subgroup generated by a
+//! assert_eq!(s3.enumerate_cosets(vec![a], 1_000).unwrap().index(), 3);
+//! ```
+//! The enumeration gives up when more cosets than the specified limit are needed,
+//! which happens when the group is infinite or the limit is too small for the presentation.
+
+use crate::group::PermutationGroup;
+use crate::Permutation;
+use crate::word::Word;
+
+pub struct Presentation {
+    generators: Vec<String>,
+    relators: Vec<Word>,
+
+    // Relators as columns of the coset table, generator i being column 2i and its inverse 2i + 1
+    relator_columns: Vec<Vec<usize>>,
+}
+
+/// Action of the generators on the cosets of a subgroup, the subgroup itself being coset 0.
+pub struct CosetTable {
+    generators: Vec<String>,
+
+    // table[c][2i] is the coset c g_i, table[c][2i + 1] is the coset c g_i'
+    table: Vec<Vec<usize>>,
+}
+
+impl Presentation {
+    /// Panics if a relator uses a generator that is not in the list.
+    pub fn create_presentation(generators: Vec<&str>, relators: Vec<Word>) -> Self {
+        let generators: Vec<String> = generators.iter().map(|name| name.to_string()).collect();
+        let relator_columns = relators.iter().map(|relator| to_columns(&generators, relator)).collect();
+
+        return Self { generators, relators, relator_columns };
+    }
+
+    pub fn generators(&self) -> &Vec<String> {
+        return &self.generators;
+    }
+
+    pub fn relators(&self) -> &Vec<Word> {
+        return &self.relators;
+    }
+
+    /// Returns true if every relator is the identity when the i-th generator is the i-th permutation,
+    /// e.g. to check a conjectured relation among the moves of a puzzle.
+    pub fn is_satisfied_by(&self, permutations: Vec<&Permutation>) -> bool {
+        let lookup = |name: &str| self.generators.iter().position(|g| g == name).map(|i| permutations[i]);
+
+        return self.relators.iter().all(|relator| relator.evaluate(&lookup).unwrap().is_identity);
+    }
+
+    /// Returns the order of the group of the presentation, or None if more than max_cosets cosets are needed.
+    pub fn order(&self, max_cosets: usize) -> Option<usize> {
+        return self.enumerate_cosets(vec![], max_cosets).map(|cosets| cosets.index());
+    }
+
+    /// Enumerates the cosets of the subgroup generated by the specified words with the Todd-Coxeter algorithm,
+    /// in the HLT strategy: every relator is traced from every coset, defining the missing cosets on the way.
+    /// Returns None if more than max_cosets cosets are defined before the table closes.
+    pub fn enumerate_cosets(&self, subgroup: Vec<Word>, max_cosets: usize) -> Option<CosetTable> {
+        let (enumeration, closed) = self.run_enumeration(subgroup, max_cosets);
+
+        return match closed {
+            true => Some(enumeration.compact(self.generators.clone())),
+            false => None,
+        };
+    }
+
+    /// Runs the enumeration until the table closes or the limit is reached,
+    /// returning the table and true if it closed. The entries of an unfinished table are still right.
+    pub(crate) fn run_enumeration(&self, subgroup: Vec<Word>, max_cosets: usize) -> (CosetEnumeration, bool) {
+        let mut enumeration = CosetEnumeration::create_enumeration(2 * self.generators.len(), max_cosets);
+        let closed = self.enumerate(&mut enumeration, &subgroup).is_some();
+
+        return (enumeration, closed);
+    }
+
+    /// Starts an enumeration of the cosets of the trivial subgroup with the single coset 0, to be continued
+    /// by `continue_enumeration` as relators are added to the presentation.
+    pub(crate) fn start_enumeration(&self, max_cosets: usize) -> CosetEnumeration {
+        return CosetEnumeration::create_enumeration(2 * self.generators.len(), max_cosets);
+    }
+
+    /// Continues an enumeration of the cosets of the trivial subgroup started with fewer relators, its entries
+    /// staying right as the group of the presentation only gets smaller. Returns true if the table closed.
+    pub(crate) fn continue_enumeration(&self, enumeration: &mut CosetEnumeration) -> bool {
+        if self.enumerate(enumeration, &[]).is_some() {
+            return true;
+        }
+
+        // The cosets found equal to others make room for the next definitions
+        return enumeration.remove_dead_cosets() && self.enumerate(enumeration, &[]).is_some();
+    }
+
+    fn enumerate(&self, enumeration: &mut CosetEnumeration, subgroup: &[Word]) -> Option<()> {
+        for word in subgroup {
+            enumeration.scan_and_fill(0, &to_columns(&self.generators, word))?;
+        }
+
+        let mut coset = 0;
+        while coset < enumeration.table.len() {
+            for relator in &self.relator_columns {
+                if !enumeration.is_alive(coset) {
+                    break;
+                }
+
+                enumeration.scan_and_fill(coset, relator)?;
+            }
+
+            for column in 0..enumeration.columns {
+                if enumeration.is_alive(coset) && enumeration.table[coset][column].is_none() {
+                    enumeration.define(coset, column)?;
+                }
+            }
+
+            coset += 1;
+        }
+
+        return Some(());
+    }
+}
+
+impl CosetTable {
+    /// Number of cosets, i.e. the index of the subgroup.
+    pub fn index(&self) -> usize {
+        return self.table.len();
+    }
+
+    /// Returns the coset reached from the specified coset by the word.
+    pub fn act(&self, coset: usize, word: &Word) -> usize {
+        return to_columns(&self.generators, word).iter().fold(coset, |c, &column| self.table[c][column]);
+    }
+
+    /// Returns the permutations of the cosets `0..index` induced by the generators, named after them.
+    /// The generator g sends the coset c to c g', so that evaluating the word `a b` gives `a * b`
+    /// with the convention of `compose`.
+    pub fn permutations(&self) -> Vec<Permutation> {
+        return self.generators.iter().enumerate()
+            .map(|(i, name)| {
+                let one_line = self.table.iter().map(|row| row[2 * i + 1]).collect();
+                Permutation::create_permutation_one_line(name.clone(), one_line)
+            })
+            .collect();
+    }
+
+    /// Returns the group generated by the permutations of the cosets, a quotient of the group of the presentation.
+    /// When the subgroup is trivial, it is isomorphic to the group of the presentation.
+    pub fn group(&self) -> PermutationGroup {
+        return PermutationGroup::create_group(self.permutations().iter().collect());
+    }
+}
+
+pub(crate) struct CosetEnumeration {
+    columns: usize,
+    max_cosets: usize,
+    table: Vec<Vec<Option<usize>>>,
+
+    // Union-find of the coincidences, a coset is alive when it is its own representative
+    parent: Vec<usize>,
+}
+
+impl CosetEnumeration {
+    fn create_enumeration(columns: usize, max_cosets: usize) -> Self {
+        return Self {
+            columns,
+            max_cosets,
+            table: vec![vec![None; columns]],
+            parent: vec![0],
+        };
+    }
+
+    /// Returns true if the word is trivial for the presentation as far as the table can tell: tracing
+    /// one of its cyclic rotations or of the ones of its inverse from coset 0 comes back to coset 0,
+    /// the rotations being conjugates of the word. False means unknown for an unfinished table.
+    pub(crate) fn proves_trivial(&mut self, generators: &[String], word: &Word) -> bool {
+        let columns = to_columns(generators, word);
+        let inverse: Vec<usize> = columns.iter().rev().map(|&column| inverse_column(column)).collect();
+
+        return [columns, inverse].iter()
+            .any(|columns| (0..columns.len().max(1)).any(|i| self.traces_back(columns[i..].iter().chain(&columns[..i]))));
+    }
+
+    fn traces_back<'a, I>(&mut self, columns: I) -> bool
+        where I: Iterator<Item = &'a usize> {
+        let mut coset = 0;
+
+        for &column in columns {
+            match self.table[coset][column] {
+                Some(image) => coset = self.representative(image),
+                None => return false,
+            }
+        }
+
+        return coset == 0;
+    }
+
+    fn is_alive(&self, coset: usize) -> bool {
+        return self.parent[coset] == coset;
+    }
+
+    /// Defines a new coset as the image of the coset by the column. Returns None if there are too many cosets.
+    fn define(&mut self, coset: usize, column: usize) -> Option<()> {
+        if self.table.len() >= self.max_cosets {
+            return None;
+        }
+
+        let new_coset = self.table.len();
+        self.table.push(vec![None; self.columns]);
+        self.parent.push(new_coset);
+
+        self.table[coset][column] = Some(new_coset);
+        self.table[new_coset][inverse_column(column)] = Some(coset);
+
+        return Some(());
+    }
+
+    /// Traces the word from the coset, forwards and backwards, defining cosets until the word closes
+    /// on the coset, and processes the coincidence if it ends elsewhere.
+    fn scan_and_fill(&mut self, coset: usize, word: &[usize]) -> Option<()> {
+        let (mut forward, mut backward) = (coset, coset);
+        let (mut i, mut j) = (0, word.len());
+
+        loop {
+            while i < j && self.table[forward][word[i]].is_some() {
+                forward = self.table[forward][word[i]].unwrap();
+                i += 1;
+            }
+
+            if i == j {
+                if forward != backward {
+                    self.coincidence(forward, backward);
+                }
+                return Some(());
+            }
+
+            while j > i && self.table[backward][inverse_column(word[j - 1])].is_some() {
+                backward = self.table[backward][inverse_column(word[j - 1])].unwrap();
+                j -= 1;
+            }
+
+            if j == i {
+                self.coincidence(forward, backward);
+                return Some(());
+            }
+
+            if j == i + 1 {
+                // Deduction: a single missing entry closes the word
+                self.table[forward][word[i]] = Some(backward);
+                self.table[backward][inverse_column(word[i])] = Some(forward);
+                return Some(());
+            }
+
+            self.define(forward, word[i])?;
+        }
+    }
+
+    fn representative(&mut self, coset: usize) -> usize {
+        let mut root = coset;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut current = coset;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+
+        return root;
+    }
+
+    /// Marks the larger of the two cosets as equal to the smaller one, queuing it to move its entries.
+    fn merge(&mut self, a: usize, b: usize, queue: &mut Vec<usize>) {
+        let (a, b) = (self.representative(a), self.representative(b));
+        if a == b {
+            return;
+        }
+
+        let (kept, removed) = (a.min(b), a.max(b));
+        self.parent[removed] = kept;
+        queue.push(removed);
+    }
+
+    /// Identifies the two cosets and all the cosets this implies, moving the entries of the removed cosets
+    /// to their representatives.
+    fn coincidence(&mut self, a: usize, b: usize) {
+        let mut queue = Vec::new();
+        self.merge(a, b, &mut queue);
+
+        let mut i = 0;
+        while i < queue.len() {
+            let removed = queue[i];
+            i += 1;
+
+            for column in 0..self.columns {
+                let Some(image) = self.table[removed][column] else { continue };
+                self.table[image][inverse_column(column)] = None;
+
+                let (coset, image) = (self.representative(removed), self.representative(image));
+                if let Some(existing) = self.table[coset][column] {
+                    self.merge(image, existing, &mut queue);
+                } else if let Some(existing) = self.table[image][inverse_column(column)] {
+                    self.merge(coset, existing, &mut queue);
+                } else {
+                    self.table[coset][column] = Some(image);
+                    self.table[image][inverse_column(column)] = Some(coset);
+                }
+            }
+        }
+    }
+
+    /// Renumbers the alive cosets from 0, in order, keeping the missing entries of an unfinished table.
+    /// Returns false if every coset was alive.
+    fn remove_dead_cosets(&mut self) -> bool {
+        let alive: Vec<usize> = (0..self.table.len()).filter(|&c| self.is_alive(c)).collect();
+        if alive.len() == self.table.len() {
+            return false;
+        }
+
+        let mut number = vec![0; self.table.len()];
+        for (i, &coset) in alive.iter().enumerate() {
+            number[coset] = i;
+        }
+
+        self.table = alive.iter()
+            .map(|&coset| {
+                (0..self.columns)
+                    .map(|column| self.table[coset][column].map(|image| number[self.representative(image)]))
+                    .collect()
+            })
+            .collect();
+        self.parent = (0..alive.len()).collect();
+
+        return true;
+    }
+
+    /// Renumbers the alive cosets from 0, in order.
+    fn compact(mut self, generators: Vec<String>) -> CosetTable {
+        let alive: Vec<usize> = (0..self.table.len()).filter(|&c| self.is_alive(c)).collect();
+
+        let mut number = vec![0; self.table.len()];
+        for (i, &coset) in alive.iter().enumerate() {
+            number[coset] = i;
+        }
+
+        let table = alive.iter()
+            .map(|&coset| {
+                (0..self.columns)
+                    .map(|column| {
+                        let image = self.table[coset][column].unwrap();
+                        number[self.representative(image)]
+                    })
+                    .collect()
+            })
+            .collect();
+
+        return CosetTable { generators, table };
+    }
+}
+
+fn inverse_column(column: usize) -> usize {
+    return column ^ 1;
+}
+
+/// Expands the word into columns of the coset table.
+fn to_columns(generators: &[String], word: &Word) -> Vec<usize> {
+    return word.letters().iter()
+        .flat_map(|(name, k)| {
+            let generator = generators.iter().position(|g| g == name)
+                .unwrap_or_else(|| panic!("Unknown generator {} in {}", name, word));
+            let column = if *k < 0 { 2 * generator + 1 } else { 2 * generator };
+
+            std::iter::repeat_n(column, k.unsigned_abs() as usize)
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::presentation::Presentation;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    fn coxeter_s4() -> Presentation {
+        let (a, b, c) = (Word::generator("a"), Word::generator("b"), Word::generator("c"));
+
+        return Presentation::create_presentation(vec!["a", "b", "c"], vec![
+            a.pow(2), b.pow(2), c.pow(2),
+            a.then(&b).pow(3), b.then(&c).pow(3), a.then(&c).pow(2),
+        ]);
+    }
+
+    #[test]
+    fn test_coset_enumeration() {
+        let s4 = coxeter_s4();
+        assert_eq!(s4.order(1_000), Some(24));
+
+        // S4 acting on the 4 cosets of S3 is its natural action
+        let (a, b, c) = (Word::generator("a"), Word::generator("b"), Word::generator("c"));
+        let cosets = s4.enumerate_cosets(vec![a.clone(), b.clone()], 1_000).unwrap();
+        assert_eq!(cosets.index(), 4);
+        assert_eq!(cosets.act(0, &a), 0);
+        assert_ne!(cosets.act(0, &c), 0);
+
+        let permutations = cosets.permutations();
+        assert!(s4.is_satisfied_by(permutations.iter().collect()));
+        assert_eq!(permutations[2].name().unwrap(), "c");
+        assert_eq!(cosets.group().order(), BigUint::from(24u32));
+
+        // The words act on the cosets with the convention of compose
+        let ab = a.then(&b);
+        let evaluated = ab.evaluate(&|name| permutations.iter().find(|p| p.name().as_deref() == Some(name))).unwrap();
+        assert_eq!(evaluated.inverse().apply(1), cosets.act(1, &ab));
+
+        // PSL(2, 7), which needs coincidences to close
+        let (x, y) = (Word::generator("x"), Word::generator("y"));
+        let psl27 = Presentation::create_presentation(vec!["x", "y"], vec![x.pow(2), y.pow(3), x.then(&y).pow(7), x.commutator(&y).pow(4)]);
+        let cosets = psl27.enumerate_cosets(vec![], 10_000).unwrap();
+        assert_eq!(cosets.index(), 168);
+        assert_eq!(cosets.group().order(), BigUint::from(168u32));
+        assert_eq!(psl27.enumerate_cosets(vec![y], 10_000).unwrap().index(), 56);
+
+        // The free group on a generator is infinite
+        let free = Presentation::create_presentation(vec!["a"], vec![]);
+        assert_eq!(free.order(100), None);
+        assert_eq!(coxeter_s4().order(10), None);
+    }
+
+    #[test]
+    fn test_relations_of_the_2x2() {
+        let moves = cube_2x2_moves();
+        let (f, r) = (Word::generator("f"), Word::generator("r"));
+
+        let relations = Presentation::create_presentation(vec!["f", "r"], vec![f.pow(4), r.pow(4), f.then(&r).pow(15)]);
+        assert!(relations.is_satisfied_by(vec![&moves[0], &moves[3]]));
+
+        let wrong = Presentation::create_presentation(vec!["f", "r"], vec![f.then(&r).pow(105).then(&f)]);
+        assert!(!wrong.is_satisfied_by(vec![&moves[0], &moves[3]]));
+
+        // The group of a face turn
+        let quarter_turns = Presentation::create_presentation(vec!["f"], vec![f.pow(4)]);
+        let cosets = quarter_turns.enumerate_cosets(vec![], 100).unwrap();
+        assert_eq!(cosets.index(), 4);
+        assert_eq!(cosets.permutations()[0].order(), BigUint::from(4u32));
+        assert_eq!(quarter_turns.enumerate_cosets(vec![f.pow(2)], 100).unwrap().index(), 2);
+    }
+}
@@ -0,0 +1,180 @@
+//! Random elements of the group generated by a set of permutations.
+//!
+//! `ProductReplacement` produces nearly uniform random elements without computing anything about the group,
+//! which is what is needed when the group is too big for a stabilizer chain, or to feed randomized algorithms.
+//! When the stabilizer chain is available, `PermutationGroup::random_element` is exactly uniform.
+//! Both are driven by a seeded generator, so that a sequence of random states can be reproduced:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::random::ProductReplacement;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//!
+//! let first: Vec<Permutation> = ProductReplacement::new(vec![&f, &r], 42).take(5).collect();
+//! let second: Vec<Permutation> = ProductReplacement::new(vec![&f, &r], 42).take(5).collect();
+//! assert_eq!(first, second);
+//! ```
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use crate::group::{inverse, mul, to_degree};
+use crate::Permutation;
+
+/// Number of steps done before the first element is returned, so that it doesn't look like the generators.
+const WARM_UP_STEPS: usize = 50;
+
+/// Minimal number of slots of the state.
+const MIN_STATE_SIZE: usize = 10;
+
+/// Product replacement with an accumulator, also known as Rattle.
+///
+/// The state is a list of elements generating the group, initially the generators repeated.
+/// At each step a random slot is multiplied by another slot or its inverse, and the accumulator
+/// by the new value of the slot. The accumulator is the random element.
+pub struct ProductReplacement {
+    degree: usize,
+    state: Vec<Vec<usize>>,
+    accumulator: Vec<usize>,
+    rng: StdRng,
+}
+
+impl ProductReplacement {
+    pub fn new(generators: Vec<&Permutation>, seed: u64) -> Self {
+        let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0);
+        let generators: Vec<Vec<usize>> = generators.iter()
+            .map(|g| to_degree(g, degree))
+            .collect();
+
+        let state = if generators.is_empty() {
+            Vec::new()
+        } else {
+            let size = (2 * generators.len()).max(MIN_STATE_SIZE);
+            generators.iter().cycle().take(size).cloned().collect()
+        };
+
+        let mut product_replacement = Self {
+            degree,
+            state,
+            accumulator: Vec::from_iter(0..degree),
+            rng: StdRng::seed_from_u64(seed),
+        };
+
+        for _ in 0..WARM_UP_STEPS {
+            product_replacement.step();
+        }
+
+        return product_replacement;
+    }
+
+    /// Largest point moved by the generators plus one.
+    pub fn degree(&self) -> usize {
+        return self.degree;
+    }
+
+    /// Returns the next random element, unnamed.
+    pub fn random_element(&mut self) -> Permutation {
+        self.step();
+
+        return Permutation::from_one_line(self.accumulator.clone());
+    }
+
+    fn step(&mut self) {
+        if self.state.is_empty() {
+            return;
+        }
+
+        let s = self.rng.random_range(0..self.state.len());
+        let mut t = self.rng.random_range(0..self.state.len() - 1);
+        if t >= s {
+            t += 1;
+        }
+
+        let other = if self.rng.random_bool(0.5) {
+            inverse(&self.state[t])
+        } else {
+            self.state[t].clone()
+        };
+
+        self.state[s] = if self.rng.random_bool(0.5) {
+            mul(&self.state[s], &other)
+        } else {
+            mul(&other, &self.state[s])
+        };
+
+        self.accumulator = mul(&self.accumulator, &self.state[s]);
+    }
+}
+
+impl Iterator for ProductReplacement {
+    type Item = Permutation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return Some(self.random_element());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::*;
+    use crate::group::PermutationGroup;
+    use crate::random::ProductReplacement;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_product_replacement() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+
+        let elements: Vec<Permutation> = ProductReplacement::new(moves.iter().collect(), 1).take(100).collect();
+        assert!(elements.iter().all(|element| group.contains(element)));
+        assert!(elements.iter().all(|element| element.name().is_none()));
+
+        // Out of 88 million elements, collisions are very unlikely
+        assert_eq!(elements.iter().collect::<HashSet<_>>().len(), 100);
+
+        assert_eq!(elements, ProductReplacement::new(moves.iter().collect(), 1).take(100).collect::<Vec<_>>());
+        assert_ne!(elements, ProductReplacement::new(moves.iter().collect(), 2).take(100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_product_replacement_small_groups() {
+        let moves = cube_2x2_moves();
+        let f = &moves[0];
+
+        let mut no_generators = ProductReplacement::new(vec![], 0);
+        assert!(no_generators.random_element().is_identity);
+
+        // Every element of the cyclic group of f is eventually reached
+        let powers: HashSet<Permutation> = ProductReplacement::new(vec![f], 0).take(100).collect();
+        assert_eq!(powers, HashSet::from([Permutation::identity(), f.clone(), f.pow(2), f.pow(3)]));
+    }
+
+    #[test]
+    fn test_uniform_random_element() {
+        let moves = cube_2x2_moves();
+        let group = PermutationGroup::create_group(moves.iter().collect());
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let elements: Vec<Permutation> = (0..100).map(|_| group.random_element(&mut rng)).collect();
+        assert!(elements.iter().all(|element| group.contains(element)));
+        assert_eq!(elements.iter().collect::<HashSet<_>>().len(), 100);
+
+        // The 15 elements of the cyclic group generated by f r are each drawn about 100 times
+        let fr = compose(&moves[0], &moves[3]);
+        let cyclic_group = PermutationGroup::create_group(vec![&fr]);
+        let mut counts = HashMap::new();
+        for _ in 0..1500 {
+            *counts.entry(cyclic_group.random_element(&mut rng)).or_insert(0) += 1;
+        }
+
+        assert_eq!(BigUint::from(counts.len()), cyclic_group.order());
+        assert!(counts.values().all(|&count| (50..150).contains(&count)), "{:?}", counts.values());
+    }
+}
@@ -0,0 +1,352 @@
+//! Bijections between states and integers, to index pattern databases and dense lookup tables.
+//!
+//! - permutations of n points and `0..n!`, in lexicographic order with the Lehmer code,
+//!   or in linear time but without order with the Myrvold-Ruskey algorithm,
+//! - orientation vectors of n pieces with k orientations and `0..k^n`,
+//! - subsets of k points among n and `0..C(n, k)`,
+//! - partial permutations, i.e. the images of k pieces among n positions, and `0..n!/(n-k)!`.
+//!
+//! Ranks are `u64`, which holds 20! but not 21!: ranking returns `PermutationError::RankTooLarge` when the number
+//! of states doesn't fit. A permutation can also be ranked on a subset of its points,
+//! e.g. the positions of the corners of the 2x2 after a move:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::rank::{lehmer_rank, lehmer_unrank};
+//!
+//! assert_eq!(lehmer_rank(&[0, 1, 2]), Ok(0));
+//! assert_eq!(lehmer_rank(&[2, 1, 0]), Ok(5));
+//! assert_eq!(lehmer_unrank(5, 3), vec![2, 1, 0]);
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! assert_eq!(f.rank_on(&[1, 2, 3, 4]), Ok(18));
+//! assert_eq!(Permutation::unrank_on(18, &[1, 2, 3, 4]), Permutation::from_cycles(vec![vec![1, 4, 3, 2]]));
+//! ```
+
+use crate::error::PermutationError;
+use crate::Permutation;
+
+/// Returns n!, or None if it doesn't fit in a u64, i.e. from n = 21 on.
+pub fn factorial(n: usize) -> Option<u64> {
+    return (1..=n as u64).try_fold(1u64, |product, i| product.checked_mul(i));
+}
+
+/// Returns the number of subsets of k points among n, or None if it doesn't fit in a u64.
+pub fn binomial(n: usize, k: usize) -> Option<u64> {
+    if k > n {
+        return Some(0);
+    }
+
+    // Each partial product is itself a binomial coefficient, so the division is exact
+    return (0..k.min(n - k) as u128)
+        .try_fold(1u128, |c, i| {
+            let next = c * (n as u128 - i) / (i + 1);
+            u64::try_from(next).ok().map(u128::from)
+        })
+        .map(|c| c as u64);
+}
+
+/// Returns the rank in `0..n!` of a permutation of `0..n` given by its images, in lexicographic order:
+/// the digits of the Lehmer code count the smaller images on the right. Returns an error if n! doesn't fit in a u64.
+pub fn lehmer_rank(images: &[usize]) -> Result<u64, PermutationError> {
+    let n = images.len();
+    factorial(n).ok_or(PermutationError::RankTooLarge { pieces: n })?;
+
+    let mut rank = 0;
+    for i in 0..n {
+        let smaller_on_the_right = images[(i + 1)..].iter().filter(|&&image| image < images[i]).count();
+        rank = rank * (n - i) as u64 + smaller_on_the_right as u64;
+    }
+
+    return Ok(rank);
+}
+
+/// Returns the permutation of `0..n` of the specified lexicographic rank, see `lehmer_rank`.
+pub fn lehmer_unrank(rank: u64, n: usize) -> Vec<usize> {
+    return partial_permutation_unrank(rank, n, n);
+}
+
+/// Returns the rank in `0..n!` of a permutation of `0..n` with the Myrvold-Ruskey algorithm,
+/// in linear time. The ranks are not in lexicographic order. Returns an error if n! doesn't fit in a u64.
+pub fn myrvold_ruskey_rank(images: &[usize]) -> Result<u64, PermutationError> {
+    factorial(images.len()).ok_or(PermutationError::RankTooLarge { pieces: images.len() })?;
+
+    let mut images = images.to_vec();
+    let mut inverse = vec![0; images.len()];
+    for (i, &image) in images.iter().enumerate() {
+        inverse[image] = i;
+    }
+
+    // rank(n) = s_n + n * rank(n - 1), where s_n is the image of the last point, swapped away afterwards
+    let mut digits = Vec::with_capacity(images.len());
+    for m in (1..=images.len()).rev() {
+        let s = images[m - 1];
+        digits.push(s as u64);
+
+        images.swap(m - 1, inverse[m - 1]);
+        inverse.swap(s, m - 1);
+    }
+
+    return Ok(digits.iter().enumerate().rev()
+        .fold(0, |rank, (i, &s)| s + (images.len() - i) as u64 * rank));
+}
+
+/// Returns the permutation of `0..n` of the specified rank, see `myrvold_ruskey_rank`.
+pub fn myrvold_ruskey_unrank(rank: u64, n: usize) -> Vec<usize> {
+    let mut images = Vec::from_iter(0..n);
+    let mut rank = rank;
+
+    for m in (1..=n).rev() {
+        images.swap(m - 1, (rank % m as u64) as usize);
+        rank /= m as u64;
+    }
+
+    return images;
+}
+
+/// Returns the rank in `0..k^n` of the orientations of n pieces, each in `0..k`, read as a number in base k.
+/// Returns an error if k^n doesn't fit in a u64.
+pub fn orientation_rank(orientations: &[usize], k: usize) -> Result<u64, PermutationError> {
+    u32::try_from(orientations.len()).ok()
+        .and_then(|n| (k as u64).checked_pow(n))
+        .ok_or(PermutationError::RankTooLarge { pieces: orientations.len() })?;
+
+    return Ok(orientations.iter().fold(0, |rank, &orientation| rank * k as u64 + orientation as u64));
+}
+
+/// Returns the orientations of n pieces with k orientations of the specified rank, see `orientation_rank`.
+pub fn orientation_unrank(rank: u64, k: usize, n: usize) -> Vec<usize> {
+    let mut orientations = vec![0; n];
+    let mut rank = rank;
+
+    for orientation in orientations.iter_mut().rev() {
+        *orientation = (rank % k as u64) as usize;
+        rank /= k as u64;
+    }
+
+    return orientations;
+}
+
+/// Returns the rank in `0..C(n, k)` of a subset of k points, given in increasing order,
+/// in colexicographic order: the rank doesn't depend on n. Returns an error if C(n, k) doesn't fit in a u64,
+/// n being one more than the last point.
+pub fn combination_rank(points: &[usize]) -> Result<u64, PermutationError> {
+    let too_large = PermutationError::RankTooLarge { pieces: points.len() };
+    binomial(points.last().map_or(0, |last| last + 1), points.len()).ok_or(too_large.clone())?;
+
+    return points.iter().enumerate()
+        .map(|(i, &point)| binomial(point, i + 1))
+        .sum::<Option<u64>>()
+        .ok_or(too_large);
+}
+
+/// Returns the subset of k points, in increasing order, of the specified rank, see `combination_rank`.
+pub fn combination_unrank(rank: u64, k: usize) -> Vec<usize> {
+    let mut points = vec![0; k];
+    let mut rank = rank;
+
+    for i in (1..=k).rev() {
+        let mut point = i - 1;
+        while binomial(point + 1, i).is_some_and(|count| count <= rank) {
+            point += 1;
+        }
+
+        points[i - 1] = point;
+        rank -= binomial(point, i).unwrap();
+    }
+
+    return points;
+}
+
+/// Returns the rank in `0..n!/(n-k)!` of the distinct images in `0..n` of k pieces, in lexicographic order,
+/// e.g. the positions of the 4 edges of a layer among the 12 edge positions. Returns an error if n!/(n-k)! doesn't fit in a u64.
+pub fn partial_permutation_rank(images: &[usize], n: usize) -> Result<u64, PermutationError> {
+    (((n + 1).saturating_sub(images.len()))..=n).try_fold(1u64, |count, i| count.checked_mul(i as u64))
+        .ok_or(PermutationError::RankTooLarge { pieces: images.len() })?;
+
+    let mut used = vec![false; n];
+    let mut rank = 0;
+
+    for (i, &image) in images.iter().enumerate() {
+        let smaller_unused = (0..image).filter(|&x| !used[x]).count();
+        rank = rank * (n - i) as u64 + smaller_unused as u64;
+        used[image] = true;
+    }
+
+    return Ok(rank);
+}
+
+/// Returns the images of k pieces among n positions of the specified rank, see `partial_permutation_rank`.
+pub fn partial_permutation_unrank(rank: u64, k: usize, n: usize) -> Vec<usize> {
+    let mut digits = vec![0; k];
+    let mut rank = rank;
+
+    for i in (0..k).rev() {
+        digits[i] = (rank % (n - i) as u64) as usize;
+        rank /= (n - i) as u64;
+    }
+
+    let mut unused = Vec::from_iter(0..n);
+
+    return digits.iter().map(|&digit| unused.remove(digit)).collect();
+}
+
+impl Permutation {
+    /// Returns the lexicographic rank in `0..n!` of the permutation restricted to the n specified points,
+    /// the i-th point being numbered i. Returns an error if a point is not sent onto one of the points, or if n! doesn't fit in a u64.
+    pub fn rank_on(&self, points: &[usize]) -> Result<u64, PermutationError> {
+        return lehmer_rank(&local_images(points, |point| self.apply(point), points)?);
+    }
+
+    /// Returns the permutation of the specified points of the specified rank, see `rank_on`.
+    /// Every other point is fixed.
+    pub fn unrank_on(rank: u64, points: &[usize]) -> Permutation {
+        let images = lehmer_unrank(rank, points.len());
+
+        let degree = points.iter().max().map_or(0, |max| max + 1);
+        let mut one_line = Vec::from_iter(0..degree);
+        for (i, &point) in points.iter().enumerate() {
+            one_line[point] = points[images[i]];
+        }
+
+        return Permutation::from_one_line(one_line);
+    }
+
+    /// Ranks the permutation restricted to pieces made of several points, e.g. the corners of the 2x2
+    /// given by `smallest_blocks`. Returns the rank in `0..n!` of the permutation of the n pieces,
+    /// and the rank in `0..k^n` of their orientations, k being the number of points of a piece:
+    /// the orientation of a piece is the index, in the piece it is sent to, of the image of its first point.
+    /// Returns an error if a piece is not sent onto a piece, or if the ranks don't fit in a u64.
+    pub fn rank_on_blocks(&self, blocks: &[Vec<usize>]) -> Result<(u64, u64), PermutationError> {
+        let first_points: Vec<usize> = blocks.iter().map(|block| block[0]).collect();
+        let block_of = |point: usize| blocks.iter().position(|block| block.contains(&point));
+
+        let mut orientations = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let image = self.apply(block[0]);
+            let target = block_of(image).ok_or(PermutationError::SubsetNotPreserved { point: block[0], image })?;
+
+            for &point in block {
+                if !blocks[target].contains(&self.apply(point)) {
+                    return Err(PermutationError::SubsetNotPreserved { point, image: self.apply(point) });
+                }
+            }
+
+            orientations.push(blocks[target].iter().position(|&p| p == image).unwrap());
+        }
+
+        let block_images = local_images(&first_points, |point| blocks[block_of(self.apply(point)).unwrap()][0], &first_points)?;
+        let k = blocks.first().map_or(1, |block| block.len());
+
+        return Ok((lehmer_rank(&block_images)?, orientation_rank(&orientations, k)?));
+    }
+}
+
+/// Returns the indices in `targets` of the images of the points.
+fn local_images<F>(points: &[usize], apply: F, targets: &[usize]) -> Result<Vec<usize>, PermutationError>
+    where F: Fn(usize) -> usize {
+    return points.iter()
+        .map(|&point| {
+            let image = apply(point);
+            targets.iter().position(|&target| target == image).ok_or(PermutationError::SubsetNotPreserved { point, image })
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::block::smallest_blocks;
+    use crate::error::PermutationError;
+    use crate::group::PermutationGroup;
+    use crate::rank::*;
+    use rand::SeedableRng;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_bijections() {
+        let n = 6;
+
+        for rank in 0..factorial(n).unwrap() {
+            let images = lehmer_unrank(rank, n);
+            assert_eq!(lehmer_rank(&images), Ok(rank));
+
+            let images = myrvold_ruskey_unrank(rank, n);
+            assert_eq!(myrvold_ruskey_rank(&images), Ok(rank));
+        }
+
+        // Lexicographic order
+        let permutations: Vec<Vec<usize>> = (0..factorial(n).unwrap()).map(|rank| lehmer_unrank(rank, n)).collect();
+        assert!(permutations.windows(2).all(|pair| pair[0] < pair[1]));
+
+        for rank in 0..3u64.pow(7) {
+            assert_eq!(orientation_rank(&orientation_unrank(rank, 3, 7), 3), Ok(rank));
+        }
+        assert_eq!(orientation_unrank(5, 3, 3), vec![0, 1, 2]);
+
+        assert_eq!(binomial(12, 4), Some(495));
+        assert_eq!(binomial(3, 5), Some(0));
+        for rank in 0..binomial(12, 4).unwrap() {
+            let points = combination_unrank(rank, 4);
+            assert!(points.windows(2).all(|pair| pair[0] < pair[1]) && points[3] < 12);
+            assert_eq!(combination_rank(&points), Ok(rank));
+        }
+
+        for rank in 0..(12 * 11 * 10 * 9) {
+            let images = partial_permutation_unrank(rank, 4, 12);
+            assert_eq!(partial_permutation_rank(&images, 12), Ok(rank));
+        }
+        assert_eq!(partial_permutation_unrank(0, 3, 12), vec![0, 1, 2]);
+        assert_eq!(partial_permutation_unrank(12 * 11 * 10 - 1, 3, 12), vec![11, 10, 9]);
+
+        // The ranks of too many pieces don't fit in a u64
+        let too_large = |pieces| Err(PermutationError::RankTooLarge { pieces });
+        let identity: Vec<usize> = (0..21).collect();
+        assert_eq!(factorial(20), Some(2_432_902_008_176_640_000));
+        assert_eq!(factorial(21), None);
+        assert_eq!(lehmer_rank(&identity[..20]), Ok(0));
+        assert_eq!(lehmer_rank(&identity), too_large(21));
+        assert_eq!(myrvold_ruskey_rank(&identity), too_large(21));
+        assert_eq!(partial_permutation_rank(&identity, 21), too_large(21));
+        assert_eq!(partial_permutation_rank(&identity[..10], 30), Ok(0));
+        assert_eq!(orientation_rank(&[0; 41], 3), too_large(41));
+        assert_eq!(orientation_rank(&[2; 40], 3), Ok(3u64.pow(40) - 1));
+        assert_eq!(binomial(68, 34), None);
+        assert_eq!(binomial(66, 33), Some(7_219_428_434_016_265_740));
+        let points: Vec<usize> = (40..74).collect();
+        assert_eq!(combination_rank(&points), too_large(34));
+        let points: Vec<usize> = (33..66).collect();
+        assert_eq!(combination_unrank(combination_rank(&points).unwrap(), 33), points);
+        assert_eq!(Permutation::identity().rank_on(&identity), too_large(21));
+    }
+
+    #[test]
+    fn test_rank_on_corners() {
+        let moves = cube_2x2_moves();
+        let corners = smallest_blocks(1, moves.iter().collect());
+
+        assert_eq!(Permutation::identity().rank_on_blocks(&corners), Ok((0, 0)));
+
+        // Every state of the corners gets a distinct pair of ranks
+        let group = PermutationGroup::create_group(vec![&moves[0], &moves[2], &moves[3]]);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut ranks = std::collections::HashMap::new();
+        for _ in 0..2000 {
+            let element = group.random_element(&mut rng);
+            let (permutation_rank, orientation) = element.rank_on_blocks(&corners).unwrap();
+            assert!(permutation_rank < factorial(8).unwrap() && orientation < 3u64.pow(8));
+
+            let previous = ranks.insert((permutation_rank, orientation), element.clone());
+            assert!(previous.is_none_or(|previous| previous == element));
+        }
+
+        // Only f and b, which doesn't touch the front face, keep the front facelets on the front face
+        let face = [1, 2, 3, 4];
+        for move_ in &moves {
+            let restricted = move_.rank_on(&face);
+            assert_eq!(restricted.is_ok(), ["f", "b"].contains(&move_.name().unwrap().as_str()));
+        }
+        assert_eq!(moves[5].rank_on(&face), Ok(0));
+        assert_eq!(moves[2].rank_on(&face), Err(PermutationError::SubsetNotPreserved { point: 1, image: 21 }));
+        assert_eq!(Permutation::unrank_on(moves[0].rank_on(&face).unwrap(), &face), Permutation::from_cycles(vec![vec![1, 4, 3, 2]]));
+    }
+}
@@ -0,0 +1,300 @@
+//! Relations among the moves of a puzzle, gathered into a presentation of its group.
+//!
+//! The relations are looked for from the simplest to the longest: generators equal to the identity or to another
+//! generator, which are usually mistakes in a definition, then the orders of the moves, the pairs of moves
+//! that commute, the orders of the products of two moves, and finally the words of at most a given length
+//! that reach the same state. A relation is only kept if it doesn't follow from the ones already found,
+//! as far as their coset enumeration can tell, a single enumeration being continued as relations are found:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::relations::find_relations;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let b = Permutation::create_permutation("b".to_string(), vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]]);
+//!
+//! let relations = find_relations(vec![&f, &b], 2, 1_000);
+//! let relators: Vec<String> = relations.presentation().relators().iter().map(|r| r.to_string()).collect();
+//! assert_eq!(relators, vec!["f4", "b4", "[f, b]"]);
+//! assert!(relations.is_complete());
+//! ```
+//! The presentation is checked to define the group when the coset enumeration of the whole group fits in
+//! the specified number of cosets, which is only the case for small groups.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigUint;
+
+use crate::compact::{SearchPermutation, SearchState, SEARCH_CAPACITY};
+use crate::group::PermutationGroup;
+use crate::presentation::{CosetEnumeration, Presentation};
+use crate::Permutation;
+use crate::word::Word;
+
+pub struct Relations {
+    presentation: Presentation,
+    identities: Vec<String>,
+    equal_generators: Vec<(String, String)>,
+    inverse_generators: Vec<(String, String)>,
+    is_complete: bool,
+}
+
+impl Relations {
+    /// Generators and relators found, every relator holding for the generators.
+    pub fn presentation(&self) -> &Presentation {
+        return &self.presentation;
+    }
+
+    /// Generators equal to the identity.
+    pub fn identities(&self) -> &Vec<String> {
+        return &self.identities;
+    }
+
+    /// Pairs of distinct generators that are the same permutation.
+    pub fn equal_generators(&self) -> &Vec<(String, String)> {
+        return &self.equal_generators;
+    }
+
+    /// Pairs of generators that are the inverse of each other.
+    pub fn inverse_generators(&self) -> &Vec<(String, String)> {
+        return &self.inverse_generators;
+    }
+
+    /// Returns true if the presentation was checked to define the group, i.e. the order of the group of the
+    /// presentation, computed by coset enumeration, is the order of the group of the generators.
+    pub fn is_complete(&self) -> bool {
+        return self.is_complete;
+    }
+}
+
+/// Looks for relations among the generators, unnamed generators being named g0, g1... by their index,
+/// with words of at most max_length moves. The partial coset enumerations are limited to max_cosets cosets.
+pub fn find_relations(generators: Vec<&Permutation>, max_length: usize, max_cosets: usize) -> Relations {
+    let names: Vec<String> = generators.iter().enumerate()
+        .map(|(i, g)| g.name().unwrap_or_else(|| format!("g{}", i)))
+        .collect();
+    let letters: Vec<Word> = names.iter().map(|name| Word::generator(name)).collect();
+
+    let mut search = RelationSearch {
+        names: names.clone(),
+        relators: Vec::new(),
+        enumeration: Presentation::create_presentation(names.iter().map(|name| name.as_str()).collect(), vec![]).start_enumeration(max_cosets),
+    };
+
+    let mut identities = Vec::new();
+    let mut equal_generators = Vec::new();
+    let mut inverse_generators = Vec::new();
+
+    for (i, g) in generators.iter().enumerate() {
+        if g.is_identity {
+            identities.push(names[i].clone());
+            search.add_if_new(letters[i].clone());
+        }
+    }
+
+    for (i, a) in generators.iter().enumerate() {
+        for (j, b) in generators.iter().enumerate().skip(i + 1) {
+            if a.is_identity || b.is_identity {
+                continue;
+            }
+
+            if a == b {
+                equal_generators.push((names[i].clone(), names[j].clone()));
+                search.add_if_new(letters[i].then(&letters[j].inverse()));
+            } else if **a == b.inverse() {
+                inverse_generators.push((names[i].clone(), names[j].clone()));
+                search.add_if_new(letters[i].then(&letters[j]));
+            }
+        }
+    }
+
+    // An order that doesn't fit in an i64 gives a relation far too long to be of use
+    for (i, g) in generators.iter().enumerate() {
+        if let Ok(order) = i64::try_from(g.order()) {
+            search.add_if_new(letters[i].pow(order));
+        }
+    }
+
+    for (i, a) in generators.iter().enumerate() {
+        for (j, b) in generators.iter().enumerate().skip(i + 1) {
+            if a.compose(b) == b.compose(a) {
+                search.add_if_new(letters[i].commutator(&letters[j]));
+            }
+        }
+    }
+
+    for (i, a) in generators.iter().enumerate() {
+        for (j, b) in generators.iter().enumerate().skip(i + 1) {
+            let product = a.compose(b);
+
+            // The order of the product of commuting moves follows from their orders
+            if product != b.compose(a) {
+                if let Ok(order) = i64::try_from(product.order()) {
+                    search.add_if_new(letters[i].then(&letters[j]).pow(order));
+                }
+            }
+        }
+    }
+
+    search.add_colliding_words(&generators, &letters, max_length);
+
+    let presentation = search.presentation();
+    let order = PermutationGroup::create_group(generators.clone()).order();
+    let is_complete = presentation.order(max_cosets).is_some_and(|presentation_order| BigUint::from(presentation_order) == order);
+
+    return Relations {
+        presentation,
+        identities,
+        equal_generators,
+        inverse_generators,
+        is_complete,
+    };
+}
+
+struct RelationSearch {
+    names: Vec<String>,
+    relators: Vec<Word>,
+
+    // Enumeration of the cosets of the trivial subgroup, continued every time a relator is added
+    enumeration: CosetEnumeration,
+}
+
+impl RelationSearch {
+    fn presentation(&self) -> Presentation {
+        return Presentation::create_presentation(self.names.iter().map(|name| name.as_str()).collect(), self.relators.clone());
+    }
+
+    /// Adds the relator unless the relators found so far imply it. Returns true if it was added.
+    fn add_if_new(&mut self, relator: Word) -> bool {
+        if relator.letters().is_empty() {
+            return false;
+        }
+
+        if self.enumeration.proves_trivial(&self.names, &relator) {
+            return false;
+        }
+
+        self.relators.push(relator);
+        self.presentation().continue_enumeration(&mut self.enumeration);
+
+        return true;
+    }
+
+    /// Walks the words by breadth first search, up to max_length moves. A word reaching a state
+    /// already reached by a shorter word gives the relation word = shorter word.
+    fn add_colliding_words(&mut self, generators: &[&Permutation], letters: &[Word], max_length: usize) {
+        let moves: Vec<(Word, Permutation)> = generators.iter().zip(letters)
+            .flat_map(|(g, letter)| [(letter.clone(), (*g).clone()), (letter.inverse(), g.inverse())])
+            .collect();
+
+        let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0);
+        match degree <= SEARCH_CAPACITY {
+            true => self.walk_words::<SearchPermutation>(&moves, max_length),
+            false => self.walk_words::<Permutation>(&moves, max_length),
+        }
+    }
+
+    fn walk_words<S: SearchState>(&mut self, moves: &[(Word, Permutation)], max_length: usize) {
+        let moves: Vec<(&Word, S)> = moves.iter().map(|(letter, permutation)| (letter, S::from_permutation(permutation))).collect();
+        let identity = S::from_permutation(&Permutation::identity());
+
+        let mut reached: HashMap<S, Word> = HashMap::from([(identity.clone(), Word::Identity)]);
+        let mut layer = vec![(Word::Identity, identity)];
+
+        for _ in 0..max_length {
+            let mut next_layer = Vec::new();
+
+            for (word, state) in &layer {
+                for (letter, permutation) in &moves {
+                    let next_state = state.then(permutation);
+
+                    match reached.get(&next_state) {
+                        Some(shorter_word) => {
+                            self.add_if_new(word.then(letter).then(&shorter_word.inverse()));
+                        }
+                        None => {
+                            let next_word = word.then(letter);
+                            reached.insert(next_state.clone(), next_word.clone());
+                            next_layer.push((next_word, next_state));
+                        }
+                    }
+                }
+            }
+
+            layer = next_layer;
+        }
+    }
+}
+
+impl Display for Relations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Generators: {}", self.presentation.generators().join(", "))?;
+
+        for name in &self.identities {
+            writeln!(f, "Warning: {} is the identity", name)?;
+        }
+        for (a, b) in &self.equal_generators {
+            writeln!(f, "Warning: {} and {} are equal", a, b)?;
+        }
+        for (a, b) in &self.inverse_generators {
+            writeln!(f, "Warning: {} and {} are inverse of each other", a, b)?;
+        }
+
+        writeln!(f, "Relators:")?;
+        for relator in self.presentation.relators() {
+            writeln!(f, "  {}", relator)?;
+        }
+
+        return match self.is_complete {
+            true => write!(f, "The relators define the group"),
+            false => write!(f, "The relators were not checked to define the group"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::relations::find_relations;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_relations_of_the_2x2() {
+        let moves = cube_2x2_moves();
+        let relations = find_relations(moves.iter().collect(), 2, 2_000);
+        let relators: Vec<String> = relations.presentation().relators().iter().map(|r| r.to_string()).collect();
+
+        assert!(relations.presentation().is_satisfied_by(moves.iter().collect()));
+        assert!(relators.contains(&"f4".to_string()));
+        assert!(relators.contains(&"[f, b]".to_string()));
+        assert!(relators.contains(&"(f r)15".to_string()));
+        assert!(!relators.contains(&"[b, f]".to_string()));
+        assert!(relations.identities().is_empty() && relations.equal_generators().is_empty());
+        assert!(!relations.is_complete());
+    }
+
+    #[test]
+    fn test_mistakes_in_the_moves() {
+        let moves = cube_2x2_moves();
+        let f_again = Permutation::create_permutation("g".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+        let fi = moves[0].inverse().with_word(Word::generator("fi"));
+        let nothing = Permutation::create_permutation("x".to_string(), vec![]);
+
+        let relations = find_relations(vec![&moves[0], &f_again, &fi, &nothing], 2, 1_000);
+        assert_eq!(relations.identities(), &vec!["x".to_string()]);
+        assert_eq!(relations.equal_generators(), &vec![("f".to_string(), "g".to_string())]);
+        assert_eq!(relations.inverse_generators(), &vec![("f".to_string(), "fi".to_string()), ("g".to_string(), "fi".to_string())]);
+        assert!(relations.is_complete());
+
+        let report = relations.to_string();
+        assert!(report.contains("Warning: f and g are equal"));
+        assert!(report.ends_with("The relators define the group"));
+
+        // S3 as the group of a 2-cycle and a 3-cycle, unnamed
+        let a = Permutation::from_cycles(vec![vec![1, 2]]);
+        let b = Permutation::from_cycles(vec![vec![1, 2, 3]]);
+        let relations = find_relations(vec![&a, &b], 3, 1_000);
+        assert_eq!(relations.presentation().generators(), &vec!["g0".to_string(), "g1".to_string()]);
+        assert!(relations.is_complete());
+    }
+}
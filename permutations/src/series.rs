@@ -0,0 +1,160 @@
+//! Normal closures, commutator subgroups and the derived series of a permutation group.
+//!
+//! The commutator subgroup is the group of the states reachable with commutators `a b a' b'` of elements
+//! of the group. Taking it again and again gives the derived series, which ends on the trivial group
+//! exactly when the group is solvable:
+//! ```
+//! use permutations::{BigUint, Permutation};
+//! use permutations::group::PermutationGroup;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//! let u = Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]);
+//! let group = PermutationGroup::create_group(vec![&f, &r, &u]);
+//!
+//! // Commutators only reach the even permutations of the corners
+//! let commutators = group.commutator_subgroup();
+//! assert_eq!(commutators.order(), BigUint::from(3_674_160u32 / 2));
+//! assert!(commutators.contains(&u.commutator(&r)));
+//! assert!(!group.is_solvable());
+//! ```
+//! The generators of the subgroups are named after the generators of the group, so that `factorize` writes
+//! their elements with commutators and conjugates.
+
+use crate::group::PermutationGroup;
+use crate::Permutation;
+
+impl PermutationGroup {
+    /// Returns true if every generator of the other group is an element of this group.
+    pub fn is_subgroup(&self, other: &PermutationGroup) -> bool {
+        return other.generators().iter().all(|g| self.contains(g));
+    }
+
+    /// Returns true if the subgroup is normal in this group, i.e. if it is stable by conjugation by every element.
+    /// Conjugating the generators of the subgroup by the generators of the group is enough.
+    pub fn is_normal(&self, subgroup: &PermutationGroup) -> bool {
+        return self.is_subgroup(subgroup) && self.generators().iter()
+            .all(|g| subgroup.generators().iter().all(|n| subgroup.contains(&g.conjugate(n))));
+    }
+
+    /// Returns the smallest normal subgroup of this group containing the specified permutations,
+    /// generated by their conjugates. The permutations are expected to be elements of this group.
+    pub fn normal_closure(&self, elements: Vec<&Permutation>) -> PermutationGroup {
+        let mut closure = PermutationGroup::create_group(vec![]);
+        for &element in &elements {
+            closure.add_generator(element);
+        }
+
+        // Every generator of the closure is conjugated by every generator of the group, including the new ones
+        let mut i = 0;
+        while i < closure.generators().len() {
+            for g in self.generators() {
+                let conjugate = g.conjugate(&closure.generators()[i]);
+                closure.add_generator(&conjugate);
+            }
+
+            i += 1;
+        }
+
+        return closure;
+    }
+
+    /// Returns the commutator subgroup, or derived subgroup, generated by the commutators of all the elements.
+    /// It is the normal closure of the commutators of the generators.
+    pub fn commutator_subgroup(&self) -> PermutationGroup {
+        let mut commutators = Vec::new();
+
+        for (i, a) in self.generators().iter().enumerate() {
+            for b in &self.generators()[(i + 1)..] {
+                let commutator = a.commutator(b);
+
+                if !commutator.is_identity && !commutators.contains(&commutator) {
+                    commutators.push(commutator);
+                }
+            }
+        }
+
+        return self.normal_closure(commutators.iter().collect());
+    }
+
+    /// Returns the derived series G = G_0 > G_1 > ... > G_k, G_(i+1) being the commutator subgroup of G_i,
+    /// until it stops decreasing. The first group is a copy of this group.
+    pub fn derived_series(&self) -> Vec<PermutationGroup> {
+        let mut series = vec![PermutationGroup::create_group(self.generators().iter().collect())];
+
+        loop {
+            let last = series.last().unwrap();
+            let derived = last.commutator_subgroup();
+
+            if derived.order() == last.order() {
+                return series;
+            }
+
+            series.push(derived);
+        }
+    }
+
+    /// Returns true if the derived series ends on the trivial group.
+    pub fn is_solvable(&self) -> bool {
+        return self.derived_series().last().unwrap().is_trivial();
+    }
+
+    /// Returns true if the group is its own commutator subgroup.
+    pub fn is_perfect(&self) -> bool {
+        return self.commutator_subgroup().order() == self.order();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::group::PermutationGroup;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_derived_series_of_s4() {
+        let transposition = Permutation::from_cycles(vec![vec![1, 2]]);
+        let four_cycle = Permutation::from_cycles(vec![vec![1, 2, 3, 4]]);
+        let s4 = PermutationGroup::create_group(vec![&transposition, &four_cycle]);
+
+        // S4 > A4 > V4 > 1
+        let orders: Vec<BigUint> = s4.derived_series().iter().map(|group| group.order()).collect();
+        assert_eq!(orders, vec![24u32, 12, 4, 1].into_iter().map(BigUint::from).collect::<Vec<_>>());
+        assert!(s4.is_solvable());
+        assert!(!s4.is_perfect());
+
+        let double_transposition = Permutation::from_cycles(vec![vec![1, 2], vec![3, 4]]);
+        let three_cycle = Permutation::from_cycles(vec![vec![1, 2, 3]]);
+        assert_eq!(s4.normal_closure(vec![&double_transposition]).order(), BigUint::from(4u32));
+        assert_eq!(s4.normal_closure(vec![&three_cycle]).order(), BigUint::from(12u32));
+        assert_eq!(s4.normal_closure(vec![&transposition]).order(), BigUint::from(24u32));
+        assert!(s4.normal_closure(vec![]).is_trivial());
+
+        assert!(s4.is_normal(&s4.normal_closure(vec![&double_transposition])));
+        assert!(!s4.is_normal(&PermutationGroup::create_group(vec![&transposition])));
+        assert!(!s4.is_subgroup(&PermutationGroup::create_group(vec![&Permutation::from_cycles(vec![vec![4, 5]])])));
+    }
+
+    #[test]
+    fn test_commutator_subgroup_of_the_2x2() {
+        let moves = cube_2x2_moves();
+        let (f, u, r) = (&moves[0], &moves[2], &moves[3]);
+        let group = PermutationGroup::create_group(vec![f, r, u]);
+
+        let commutators = group.commutator_subgroup();
+        assert_eq!(commutators.order(), BigUint::from(1_837_080u32));
+        assert!(group.is_normal(&commutators));
+        assert!(commutators.contains(&f.commutator(u)));
+        assert!(!commutators.contains(f));
+        assert!(commutators.contains(&f.pow(2)));
+
+        // The corner twists and the even corner permutations can't be split further by commutators
+        assert!(commutators.is_perfect());
+        assert_eq!(group.derived_series().len(), 2);
+        assert!(!group.is_solvable());
+
+        // Elements of the commutator subgroup are written with the commutators of the moves
+        let word = commutators.factorize_word(&f.pow(2)).unwrap();
+        assert_eq!(word.evaluate(&|name| moves.iter().find(|m| m.name().as_deref() == Some(name))).unwrap(), f.pow(2));
+    }
+}
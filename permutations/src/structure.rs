@@ -0,0 +1,386 @@
+//! Structure of the group of a puzzle, as a human readable report.
+//!
+//! The moved points are split in orbits, and each orbit in blocks, i.e. the pieces.
+//! For every orbit the report gives the group permuting the pieces, the kernel of this action,
+//! i.e. the orientations of the pieces that can be changed without moving them, and how far the orbit group
+//! is from the full wreath product. It also lists the parity constraints linking the orbits:
+//! ```
+//! use permutations::Permutation;
+//! use permutations::structure::StructureReport;
+//!
+//! let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
+//! let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
+//! let u = Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]);
+//!
+//! let report = StructureReport::create_report(vec![&f, &r, &u]);
+//! assert_eq!(report.orbits()[0].block_action_name(), "S7");
+//! assert_eq!(report.orbits()[0].kernel_name(), "C3^6");
+//! println!("{}", report);
+//! ```
+//! Orders are computed with `create_group_randomized`, so they are right with probability at least 1 - 2^-40.
+
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigUint;
+
+use crate::block::smallest_blocks;
+use crate::group::PermutationGroup;
+use crate::orbit::orbits;
+use crate::Permutation;
+
+pub struct StructureReport {
+    order: BigUint,
+    orbits: Vec<OrbitStructure>,
+    parity_constraints: Vec<Vec<usize>>,
+}
+
+/// Structure of the action of the group on one orbit.
+pub struct OrbitStructure {
+    points: Vec<usize>,
+    blocks: Vec<Vec<usize>>,
+    order: BigUint,
+    block_action_order: BigUint,
+    kernel_order: BigUint,
+}
+
+impl StructureReport {
+    pub fn create_report(generators: Vec<&Permutation>) -> Self {
+        let order = PermutationGroup::create_group_randomized(generators.clone(), 0).order();
+
+        let orbits: Vec<OrbitStructure> = orbits(generators.clone()).into_iter()
+            .filter(|orbit| orbit.len() > 1)
+            .map(|orbit| OrbitStructure::create_orbit_structure(orbit, &generators))
+            .collect();
+
+        // For every generator, the orbits on which it permutes the pieces oddly
+        let odd_orbits: Vec<Vec<bool>> = generators.iter()
+            .map(|g| orbits.iter().map(|orbit| !orbit.block_permutation(g).is_even()).collect())
+            .collect();
+
+        return Self {
+            order,
+            parity_constraints: parity_constraints(&odd_orbits, orbits.len()),
+            orbits,
+        };
+    }
+
+    /// Order of the whole group, i.e. the number of reachable states.
+    pub fn order(&self) -> &BigUint {
+        return &self.order;
+    }
+
+    /// The orbits of the moved points, fixed points are left out.
+    pub fn orbits(&self) -> &Vec<OrbitStructure> {
+        return &self.orbits;
+    }
+
+    /// Sets of orbits, by index in `orbits`, whose pieces are permuted with an even total parity by every element,
+    /// e.g. `[0, 1]` when corners and edges always have the same parity. The sets form a basis: every other such set
+    /// is obtained by combining some of them, an orbit appearing an even number of times being left out.
+    pub fn parity_constraints(&self) -> &Vec<Vec<usize>> {
+        return &self.parity_constraints;
+    }
+
+    /// The group is always a subgroup of the direct product of its orbit groups.
+    /// Returns true if it is the whole direct product, i.e. the orbits can be scrambled independently.
+    pub fn is_direct_product(&self) -> bool {
+        return self.order == self.orbits.iter().map(|orbit| orbit.order.clone()).product::<BigUint>();
+    }
+}
+
+impl OrbitStructure {
+    fn create_orbit_structure(points: Vec<usize>, generators: &[&Permutation]) -> Self {
+        let degree = generators.iter().map(|g| g.degree()).max().unwrap_or(0);
+        let mut in_orbit = vec![false; degree];
+        for &point in &points {
+            in_orbit[point] = true;
+        }
+
+        let restricted: Vec<Permutation> = generators.iter()
+            .map(|g| Permutation::from_one_line((0..degree).map(|x| if in_orbit[x] { g.apply(x) } else { x }).collect()))
+            .collect();
+        let order = PermutationGroup::create_group_randomized(restricted.iter().collect(), 0).order();
+
+        let mut structure = Self {
+            blocks: smallest_blocks(points[0], generators.to_vec()),
+            points,
+            order,
+            block_action_order: BigUint::from(1u32),
+            kernel_order: BigUint::from(1u32),
+        };
+
+        let block_permutations: Vec<Permutation> = generators.iter().map(|g| structure.block_permutation(g)).collect();
+        structure.block_action_order = PermutationGroup::create_group_randomized(block_permutations.iter().collect(), 0).order();
+        structure.kernel_order = &structure.order / &structure.block_action_order;
+
+        return structure;
+    }
+
+    pub fn points(&self) -> &Vec<usize> {
+        return &self.points;
+    }
+
+    /// The pieces of the orbit, single points if the group is primitive on the orbit.
+    pub fn blocks(&self) -> &Vec<Vec<usize>> {
+        return &self.blocks;
+    }
+
+    /// Order of the group induced on the orbit.
+    pub fn order(&self) -> &BigUint {
+        return &self.order;
+    }
+
+    /// Order of the group permuting the blocks.
+    pub fn block_action_order(&self) -> &BigUint {
+        return &self.block_action_order;
+    }
+
+    /// Order of the elements that leave every block in place, e.g. the corner twists.
+    pub fn kernel_order(&self) -> &BigUint {
+        return &self.kernel_order;
+    }
+
+    /// Name of the group permuting the blocks: `S8` or `A8` for the symmetric or alternating group, else its order.
+    pub fn block_action_name(&self) -> String {
+        let n = self.blocks.len();
+        let factorial: BigUint = (1..=n).map(BigUint::from).product();
+
+        return if self.block_action_order == factorial {
+            format!("S{}", n)
+        } else if n > 1 && self.block_action_order == factorial / 2u32 {
+            format!("A{}", n)
+        } else {
+            format!("group of order {}", self.block_action_order)
+        };
+    }
+
+    /// Name of the kernel of the action on the blocks.
+    /// When the blocks have a prime size p, the kernel is a p-group inside the product of the C_p rotating each block,
+    /// so it is C_p^k.
+    pub fn kernel_name(&self) -> String {
+        let block_size = self.blocks[0].len();
+
+        if self.kernel_order == BigUint::from(1u32) {
+            return "trivial".to_string();
+        }
+
+        if is_prime(block_size) {
+            if let Some(k) = exponent_of(&self.kernel_order, block_size) {
+                return if k == 1 { format!("C{}", block_size) } else { format!("C{}^{}", block_size, k) };
+            }
+        }
+
+        return format!("group of order {}", self.kernel_order);
+    }
+
+    /// Returns the permutation of the block indices induced by the element.
+    fn block_permutation(&self, element: &Permutation) -> Permutation {
+        let block_of = |point: usize| self.blocks.iter().position(|block| block.contains(&point)).unwrap();
+
+        return Permutation::from_one_line(self.blocks.iter().map(|block| block_of(element.apply(block[0]))).collect());
+    }
+}
+
+impl Display for StructureReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Order: {} = {}", self.order, factorize(&self.order))?;
+
+        for (i, orbit) in self.orbits.iter().enumerate() {
+            let block_size = orbit.blocks[0].len();
+            writeln!(f, "Orbit {}: {} points in {} blocks of {}", i, orbit.points.len(), orbit.blocks.len(), block_size)?;
+            writeln!(f, "  action on the blocks: {}", orbit.block_action_name())?;
+            writeln!(f, "  kernel: {}", orbit.kernel_name())?;
+
+            if block_size > 1 && is_prime(block_size) {
+                let wreath_order = BigUint::from(block_size).pow(orbit.blocks.len() as u32) * &orbit.block_action_order;
+                let index = wreath_order / &orbit.order;
+
+                if index == BigUint::from(1u32) {
+                    writeln!(f, "  C{} wr {}", block_size, orbit.block_action_name())?;
+                } else {
+                    writeln!(f, "  subgroup of index {} of C{} wr {}", index, block_size, orbit.block_action_name())?;
+                }
+            }
+        }
+
+        if self.parity_constraints.is_empty() {
+            writeln!(f, "Parity constraints: none")?;
+        }
+
+        for constraint in &self.parity_constraints {
+            let names: Vec<String> = constraint.iter().map(|i| i.to_string()).collect();
+
+            match constraint.len() {
+                1 => writeln!(f, "Parity constraint: the blocks of orbit {} are always permuted evenly", names[0])?,
+                2 => writeln!(f, "Parity constraint: the blocks of orbits {} and {} are permuted with the same parity", names[0], names[1])?,
+                _ => writeln!(f, "Parity constraint: the blocks of orbits {} are permuted with an even total parity", names.join(", "))?,
+            }
+        }
+
+        if self.is_direct_product() {
+            write!(f, "Direct product of the orbit groups")?;
+        } else {
+            let direct_order: BigUint = self.orbits.iter().map(|orbit| orbit.order.clone()).product();
+            write!(f, "Subdirect product of the orbit groups, of index {} in their direct product", direct_order / &self.order)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Returns a basis of the sets of orbits whose parities always add up to even, given the odd orbits of every generator.
+/// They are the kernel of the matrix of the odd orbits over GF(2), computed from its reduced row echelon form:
+/// every free orbit gives the set made of itself and of the pivot orbits it depends on.
+fn parity_constraints(odd_orbits: &[Vec<bool>], orbit_count: usize) -> Vec<Vec<usize>> {
+    let mut rows: Vec<Vec<bool>> = odd_orbits.to_vec();
+    let mut pivots: Vec<usize> = Vec::new();
+
+    for column in 0..orbit_count {
+        let rank = pivots.len();
+        let Some(pivot_row) = (rank..rows.len()).find(|&r| rows[r][column]) else {
+            continue;
+        };
+        rows.swap(rank, pivot_row);
+
+        for r in 0..rows.len() {
+            if r != rank && rows[r][column] {
+                let pivot = rows[rank].clone();
+                rows[r].iter_mut().zip(pivot).for_each(|(x, p)| *x ^= p);
+            }
+        }
+
+        pivots.push(column);
+    }
+
+    let mut constraints: Vec<Vec<usize>> = (0..orbit_count)
+        .filter(|column| !pivots.contains(column))
+        .map(|free| {
+            let mut constraint: Vec<usize> = pivots.iter().enumerate()
+                .filter(|(row, _)| rows[*row][free])
+                .map(|(_, &pivot)| pivot)
+                .collect();
+            constraint.push(free);
+            constraint.sort_unstable();
+
+            constraint
+        })
+        .collect();
+    constraints.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    return constraints;
+}
+
+fn is_prime(n: usize) -> bool {
+    return n >= 2 && (2..n).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d));
+}
+
+/// Returns k if n = p^k.
+fn exponent_of(n: &BigUint, p: usize) -> Option<u32> {
+    let mut n = n.clone();
+    let mut k = 0;
+
+    while n > BigUint::from(1u32) {
+        if &n % p != BigUint::from(0u32) {
+            return None;
+        }
+
+        n /= p;
+        k += 1;
+    }
+
+    return Some(k);
+}
+
+/// Prime factorization of a group order, e.g. `2^7 * 3^9 * 5 * 7`. The prime factors of the order of a group
+/// of permutations are smaller than the degree, so trial division ends quickly.
+fn factorize(n: &BigUint) -> String {
+    let mut n = n.clone();
+    let mut factors = Vec::new();
+
+    let mut p = 2u32;
+    while n > BigUint::from(1u32) {
+        let mut k = 0;
+        while &n % p == BigUint::from(0u32) {
+            n /= p;
+            k += 1;
+        }
+
+        match k {
+            0 => {}
+            1 => factors.push(p.to_string()),
+            _ => factors.push(format!("{}^{}", p, k)),
+        }
+
+        p += 1;
+    }
+
+    return if factors.is_empty() { "1".to_string() } else { factors.join(" * ") };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::structure::StructureReport;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_cube_2x2_structure() {
+        let moves = cube_2x2_moves();
+        let report = StructureReport::create_report(moves.iter().collect());
+
+        assert_eq!(*report.order(), BigUint::from(88_179_840u32));
+        assert_eq!(report.orbits().len(), 1);
+
+        let corners = &report.orbits()[0];
+        assert_eq!(corners.points().len(), 24);
+        assert_eq!(corners.blocks().len(), 8);
+        assert_eq!(*corners.block_action_order(), BigUint::from(40_320u32));
+        assert_eq!(*corners.kernel_order(), BigUint::from(2_187u32));
+        assert_eq!(corners.block_action_name(), "S8");
+        assert_eq!(corners.kernel_name(), "C3^7");
+
+        assert!(report.parity_constraints().is_empty());
+        assert!(report.is_direct_product());
+
+        assert_eq!(report.to_string(), "Order: 88179840 = 2^7 * 3^9 * 5 * 7\n\
+                                        Orbit 0: 24 points in 8 blocks of 3\n  \
+                                          action on the blocks: S8\n  \
+                                          kernel: C3^7\n  \
+                                          subgroup of index 3 of C3 wr S8\n\
+                                        Parity constraints: none\n\
+                                        Direct product of the orbit groups");
+    }
+
+    #[test]
+    fn test_parity_constraints() {
+        // 3-cycles only generate the alternating group
+        let a = Permutation::from_cycles(vec![vec![1, 2, 3]]);
+        let b = Permutation::from_cycles(vec![vec![2, 3, 4]]);
+        let report = StructureReport::create_report(vec![&a, &b]);
+        assert_eq!(report.orbits()[0].block_action_name(), "A4");
+        assert_eq!(report.parity_constraints(), &vec![vec![0]]);
+        assert!(report.to_string().contains("the blocks of orbit 0 are always permuted evenly"));
+
+        // Two disjoint 2-cycles moved together
+        let linked = Permutation::from_cycles(vec![vec![1, 2], vec![5, 6]]);
+        let shift = Permutation::from_cycles(vec![vec![1, 2, 3]]);
+        let report = StructureReport::create_report(vec![&linked, &shift]);
+        assert_eq!(report.orbits().len(), 2);
+        assert_eq!(report.orbits()[0].block_action_name(), "S3");
+        assert_eq!(report.orbits()[1].kernel_name(), "trivial");
+        assert_eq!(report.parity_constraints(), &vec![vec![0, 1]]);
+        assert!(!report.is_direct_product());
+    }
+
+    #[test]
+    fn test_parity_constraints_of_many_orbits() {
+        // 70 orbits of 2 points, each generator swapping the points of two neighbouring orbits
+        let generators: Vec<Permutation> = (0..69)
+            .map(|i| Permutation::from_cycles(vec![vec![2 * i + 1, 2 * i + 2], vec![2 * i + 3, 2 * i + 4]]))
+            .collect();
+        let report = StructureReport::create_report(generators.iter().collect());
+
+        assert_eq!(report.orbits().len(), 70);
+        assert_eq!(report.parity_constraints(), &vec![(0..70).collect::<Vec<usize>>()]);
+    }
+}
@@ -0,0 +1,357 @@
+//! Move expressions, recording how a permutation was obtained from named generators.
+//!
+//! A `Word` is a tree whose leaves are generator names and whose nodes are sequences, inverses, powers,
+//! conjugates and commutators. It is printed in the usual puzzle notation:
+//! `f r' u2 (f r)3 [f: r u r'] [r, u]`, with `[a: b] = a b a'` and `[a, b] = a b a' b'`.
+//!
+//! Sequences are read from left to right, like `compose`: `f r` means f then r.
+
+use std::fmt::{Display, Formatter};
+
+use crate::Permutation;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Word {
+    Identity,
+    Generator(String),
+    Inverse(Box<Word>),
+    Power(Box<Word>, i64),
+    Sequence(Vec<Word>),
+    Conjugate(Box<Word>, Box<Word>),
+    Commutator(Box<Word>, Box<Word>),
+}
+
+impl Word {
+    pub fn generator(name: &str) -> Self {
+        return Word::Generator(name.to_string());
+    }
+
+    /// Returns self followed by other. Sequences are flattened and the generators meeting at the junction
+    /// are merged, so that `f r` then `r' u` gives `f u`, without expanding the rest of the words.
+    pub fn then(&self, other: &Word) -> Self {
+        let mut terms = self.terms();
+
+        for term in other.terms() {
+            push_merged(&mut terms, term);
+        }
+
+        return Word::from_terms(terms);
+    }
+
+    /// Returns the inverse word, built from the structure of the word:
+    /// `(a b)' = b' a'`, `[a: b]' = [a: b']` and `[a, b]' = [b, a]`.
+    pub fn inverse(&self) -> Self {
+        return match self {
+            Word::Identity => Word::Identity,
+            Word::Generator(_) => Word::Inverse(Box::new(self.clone())),
+            Word::Inverse(word) => (**word).clone(),
+            Word::Power(word, k) => Word::Power(word.clone(), -k).normalized_power(),
+            Word::Sequence(words) => Word::Sequence(words.iter().rev().map(|word| word.inverse()).collect()),
+            Word::Conjugate(a, b) => Word::Conjugate(a.clone(), Box::new(b.inverse())),
+            Word::Commutator(a, b) => Word::Commutator(b.clone(), a.clone()),
+        };
+    }
+
+    pub fn pow(&self, k: i64) -> Self {
+        return match (self, k) {
+            (Word::Identity, _) | (_, 0) => Word::Identity,
+            (_, 1) => self.clone(),
+            (Word::Power(word, j), _) => match j.checked_mul(k) {
+                Some(jk) => Word::Power(word.clone(), jk).normalized_power(),
+                None => Word::Power(Box::new(self.clone()), k),
+            },
+            (Word::Inverse(word), _) => Word::Power(word.clone(), -k).normalized_power(),
+            _ => Word::Power(Box::new(self.clone()), k).normalized_power(),
+        };
+    }
+
+    /// Returns [self: other], i.e. self other self'.
+    pub fn conjugate(&self, other: &Word) -> Self {
+        return Word::Conjugate(Box::new(self.clone()), Box::new(other.clone()));
+    }
+
+    /// Returns [self, other], i.e. self other self' other'.
+    pub fn commutator(&self, other: &Word) -> Self {
+        return Word::Commutator(Box::new(self.clone()), Box::new(other.clone()));
+    }
+
+    /// Expands the word into generators with their exponents, from left to right,
+    /// cancelling `f f'` and merging `f f` into `f2`.
+    ///
+    /// The power k of anything but a generator is expanded k times, so the time taken grows with the exponents:
+    /// a word like `(f r)^(2^40)` never finishes. Parsed words are bounded by `MAX_WORD_MOVES`,
+    /// words built with `pow` are not.
+    pub fn letters(&self) -> Vec<(String, i64)> {
+        let mut letters = Vec::new();
+        self.push_letters(&mut letters, 1);
+
+        return letters;
+    }
+
+    /// Returns the fully expanded word, as a sequence of powers of generators.
+    /// Conjugates, commutators and powers of sequences are expanded, as in `letters`.
+    pub fn simplify(&self) -> Self {
+        let terms = self.letters().into_iter()
+            .map(|(name, k)| letter_word(name, k))
+            .collect();
+
+        return Word::from_terms(terms);
+    }
+
+    /// Number of moves of the simplified word, a power of a generator counting as one move.
+    /// The word is expanded, as in `letters`.
+    pub fn move_count(&self) -> usize {
+        return self.letters().len();
+    }
+
+    /// Evaluates the word, looking up the permutation of each generator by its name.
+    /// Returns None if a generator is unknown. The returned permutation remembers this word.
+    pub fn evaluate<'a, F>(&self, lookup: &F) -> Option<Permutation>
+        where F: Fn(&str) -> Option<&'a Permutation> {
+        let permutation = match self {
+            Word::Identity => Permutation::identity(),
+            Word::Generator(name) => lookup(name)?.clone(),
+            Word::Inverse(word) => word.evaluate(lookup)?.inverse(),
+            Word::Power(word, k) => word.evaluate(lookup)?.pow(*k),
+            Word::Sequence(words) => {
+                let mut result = Permutation::identity();
+                for word in words {
+                    result = result.compose(&word.evaluate(lookup)?);
+                }
+
+                result
+            }
+            Word::Conjugate(a, b) => a.evaluate(lookup)?.conjugate(&b.evaluate(lookup)?),
+            Word::Commutator(a, b) => b.evaluate(lookup)?.commutator(&a.evaluate(lookup)?),
+        };
+
+        return Some(permutation.with_word(self.clone()));
+    }
+
+    fn push_letters(&self, letters: &mut Vec<(String, i64)>, sign: i64) {
+        match self {
+            Word::Identity => {}
+            Word::Generator(name) => push_letter(letters, name.clone(), sign),
+            Word::Inverse(word) => word.push_letters(letters, -sign),
+            Word::Power(word, k) => {
+                if let Word::Generator(name) = &**word {
+                    push_letter(letters, name.clone(), sign * k);
+                } else {
+                    for _ in 0..k.abs() {
+                        word.push_letters(letters, sign * k.signum());
+                    }
+                }
+            }
+            Word::Sequence(words) => {
+                if sign > 0 {
+                    words.iter().for_each(|word| word.push_letters(letters, sign));
+                } else {
+                    words.iter().rev().for_each(|word| word.push_letters(letters, sign));
+                }
+            }
+            Word::Conjugate(a, b) => {
+                a.push_letters(letters, 1);
+                b.push_letters(letters, sign);
+                a.push_letters(letters, -1);
+            }
+            Word::Commutator(a, b) => {
+                let (a, b) = if sign > 0 { (a, b) } else { (b, a) };
+
+                a.push_letters(letters, 1);
+                b.push_letters(letters, 1);
+                a.push_letters(letters, -1);
+                b.push_letters(letters, -1);
+            }
+        }
+    }
+
+    /// The top level terms of the word, i.e. the items of a sequence or the word itself.
+    fn terms(&self) -> Vec<Word> {
+        return match self {
+            Word::Identity => Vec::new(),
+            Word::Sequence(words) => words.clone(),
+            _ => vec![self.clone()],
+        };
+    }
+
+    fn from_terms(mut terms: Vec<Word>) -> Self {
+        return match terms.len() {
+            0 => Word::Identity,
+            1 => terms.remove(0),
+            _ => Word::Sequence(terms),
+        };
+    }
+
+    /// Returns the generator and exponent of `f`, `f'` and `f2` like words, or None when the exponent overflows.
+    fn as_letter(&self) -> Option<(&String, i64)> {
+        return match self {
+            Word::Generator(name) => Some((name, 1)),
+            Word::Inverse(word) => word.as_letter().and_then(|(name, k)| Some((name, k.checked_neg()?))),
+            Word::Power(word, k) => word.as_letter().and_then(|(name, j)| Some((name, j.checked_mul(*k)?))),
+            _ => None,
+        };
+    }
+
+    fn normalized_power(self) -> Self {
+        return match self {
+            Word::Power(_, 0) => Word::Identity,
+            Word::Power(word, _) if *word == Word::Identity => Word::Identity,
+            Word::Power(word, 1) => *word,
+            Word::Power(word, -1) => Word::Inverse(word),
+            _ => self,
+        };
+    }
+}
+
+fn letter_word(name: String, k: i64) -> Word {
+    return Word::Power(Box::new(Word::Generator(name)), k).normalized_power();
+}
+
+fn push_letter(letters: &mut Vec<(String, i64)>, name: String, k: i64) {
+    if let Some((last_name, last_k)) = letters.last_mut() {
+        if let Some(sum) = last_k.checked_add(k).filter(|_| *last_name == name) {
+            *last_k = sum;
+
+            if *last_k == 0 {
+                letters.pop();
+            }
+            return;
+        }
+    }
+
+    if k != 0 {
+        letters.push((name, k));
+    }
+}
+
+fn push_merged(terms: &mut Vec<Word>, term: Word) {
+    let merged = match (terms.last().and_then(|last| last.as_letter()), term.as_letter()) {
+        (Some((last_name, j)), Some((name, k))) if last_name == name => j.checked_add(k).map(|jk| (name.clone(), jk)),
+        _ => None,
+    };
+
+    match merged {
+        Some((name, k)) => {
+            terms.pop();
+            if k != 0 {
+                terms.push(letter_word(name, k));
+            }
+        }
+        None => terms.push(term),
+    }
+}
+
+impl Display for Word {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Word::Identity => write!(f, "id"),
+            Word::Generator(name) => write!(f, "{}", name),
+            Word::Inverse(word) => write!(f, "{}'", Grouped(word)),
+            Word::Power(word, k) if *k < 0 => write!(f, "{}{}'", Grouped(word), -k),
+            Word::Power(word, k) => write!(f, "{}{}", Grouped(word), k),
+            Word::Sequence(words) => {
+                let terms: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+                write!(f, "{}", terms.join(" "))
+            }
+            Word::Conjugate(a, b) => write!(f, "[{}: {}]", a, b),
+            Word::Commutator(a, b) => write!(f, "[{}, {}]", a, b),
+        };
+    }
+}
+
+/// Puts parentheses around words that need them before a power or an inverse, e.g. `(f r)2`.
+struct Grouped<'a>(&'a Word);
+
+impl Display for Grouped<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self.0 {
+            Word::Generator(_) | Word::Conjugate(_, _) | Word::Commutator(_, _) => write!(f, "{}", self.0),
+            _ => write!(f, "({})", self.0),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::word::Word;
+    use crate::test_fixtures::cube_2x2_moves;
+
+    #[test]
+    fn test_display() {
+        let f = Word::generator("f");
+        let r = Word::generator("r");
+        let u = Word::generator("u");
+
+        assert_eq!(Word::Identity.to_string(), "id");
+        assert_eq!(f.inverse().to_string(), "f'");
+        assert_eq!(f.pow(2).to_string(), "f2");
+        assert_eq!(f.pow(-2).to_string(), "f2'");
+        assert_eq!(f.then(&r).pow(3).to_string(), "(f r)3");
+        assert_eq!(f.then(&r).inverse().to_string(), "r' f'");
+        assert_eq!(f.conjugate(&r.then(&u).then(&r.inverse())).to_string(), "[f: r u r']");
+        assert_eq!(r.commutator(&u).to_string(), "[r, u]");
+        assert_eq!(r.commutator(&u).inverse().to_string(), "[u, r]");
+        assert_eq!(Word::Inverse(Box::new(r.commutator(&u))).to_string(), "[r, u]'");
+
+        // Exponents too large to be multiplied stay nested
+        assert_eq!(f.pow(2).pow(5_000_000_000_000_000_000).to_string(), "(f2)5000000000000000000");
+    }
+
+    #[test]
+    fn test_simplify() {
+        let f = Word::generator("f");
+        let r = Word::generator("r");
+
+        assert_eq!(f.then(&f.inverse()), Word::Identity);
+        assert_eq!(f.then(&f), f.pow(2));
+        assert_eq!(f.then(&r).then(&r.inverse()).then(&f), f.pow(2));
+
+        let a_tech = Word::Sequence(vec![f.clone(), Word::generator("d").inverse(), f.inverse()]);
+        assert_eq!(a_tech.then(&a_tech.inverse()), Word::Identity);
+
+        assert_eq!(f.commutator(&r).simplify().to_string(), "f r f' r'");
+        assert_eq!(f.conjugate(&r).inverse().simplify().to_string(), "f r' f'");
+        assert_eq!(f.then(&r).pow(2).simplify().to_string(), "f r f r");
+        assert_eq!(f.then(&r).pow(-1).simplify().to_string(), "r' f'");
+        assert_eq!(Word::Sequence(vec![f.clone(), f.clone(), f.clone()]).simplify().to_string(), "f3");
+        assert_eq!(f.conjugate(&r).then(&f.conjugate(&r.inverse())).simplify(), Word::Identity);
+        assert_eq!(f.commutator(&r).move_count(), 4);
+
+        // Exponents too large to be added or multiplied stay in separate terms
+        let big = f.pow(3_000_000_000).pow(4_000_000_000);
+        assert_eq!(big.then(&f), Word::Sequence(vec![big.clone(), f.clone()]));
+        assert_eq!(f.pow(i64::MAX).then(&f), Word::Sequence(vec![f.pow(i64::MAX), f.clone()]));
+        assert_eq!(f.pow(i64::MAX).then(&f).letters(), vec![("f".to_string(), i64::MAX), ("f".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let moves = cube_2x2_moves();
+        let lookup = |name: &str| moves.iter().find(|m| m.to_string() == name);
+        let (f, u, r, d) = (&moves[0], &moves[2], &moves[3], &moves[4]);
+
+        let a_tech_right = Word::Sequence(vec![
+            Word::generator("f"), Word::generator("d").inverse(), Word::generator("f").inverse(),
+            Word::generator("d").inverse(), Word::generator("r").inverse(), Word::generator("d"), Word::generator("r")]);
+
+        let evaluated = a_tech_right.evaluate(&lookup).unwrap();
+        assert_eq!(evaluated, compose_7(f, &d.inverse(), &f.inverse(), &d.inverse(), &r.inverse(), d, r));
+        assert_eq!(evaluated.to_string(), "f d' f' d' r' d r");
+        assert_eq!(a_tech_right.inverse().evaluate(&lookup).unwrap(), evaluated.inverse());
+        assert_eq!(a_tech_right.simplify().evaluate(&lookup).unwrap(), evaluated);
+
+        let commutator = Word::generator("r").commutator(&Word::generator("u"));
+        assert_eq!(commutator.evaluate(&lookup).unwrap(), compose_4(r, u, &r.inverse(), &u.inverse()));
+        assert_eq!(commutator.evaluate(&lookup).unwrap(), commutator.simplify().evaluate(&lookup).unwrap());
+
+        let conjugate = Word::generator("f").conjugate(&commutator);
+        assert_eq!(conjugate.evaluate(&lookup).unwrap(), conjugate.simplify().evaluate(&lookup).unwrap());
+        assert_eq!(conjugate.inverse().evaluate(&lookup).unwrap(), conjugate.evaluate(&lookup).unwrap().inverse());
+
+        let power = Word::generator("f").then(&Word::generator("r")).pow(-3);
+        assert_eq!(power.evaluate(&lookup).unwrap(), power.simplify().evaluate(&lookup).unwrap());
+
+        assert_eq!(Word::generator("x").evaluate(&lookup), None);
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use colorful::{Color, Colorful};
 
 use permutations::Permutation;
+use permutations::error::PermutationError;
 
 use super::polyhedron::PolyhedronDefinition;
 
@@ -12,14 +13,19 @@ pub struct Cube2x2Definition {
 
 impl Cube2x2Definition {
     pub fn new() -> Self {
+        return Self::try_new().unwrap_or_else(|error| panic!("Invalid definition of Cube2x2Definition: {}", error));
+    }
+
+    /// Same as `new`, but returns an error instead of panicking if a move is not a valid permutation.
+    pub fn try_new() -> Result<Self, PermutationError> {
         let mut permitted_permutations = HashMap::new();
 
-        let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]]);
-        let l = Permutation::create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]]);
-        let u = Permutation::create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]]);
-        let r = Permutation::create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]]);
-        let d = Permutation::create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]]);
-        let b = Permutation::create_permutation("b".to_string(), vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]]);
+        let f = Permutation::try_create_permutation("f".to_string(), vec![vec![1, 4, 3, 2], vec![13, 42, 31, 24], vec![14, 43, 32, 21]])?;
+        let l = Permutation::try_create_permutation("l".to_string(), vec![vec![1, 11, 53, 31], vec![4, 14, 52, 34], vec![41, 44, 43, 42]])?;
+        let u = Permutation::try_create_permutation("u".to_string(), vec![vec![1, 21, 51, 41], vec![2, 22, 52, 42], vec![11, 14, 13, 12]])?;
+        let r = Permutation::try_create_permutation("r".to_string(), vec![vec![2, 32, 54, 12], vec![3, 33, 51, 13], vec![21, 24, 23, 22]])?;
+        let d = Permutation::try_create_permutation("d".to_string(), vec![vec![3, 43, 53, 23], vec![4, 44, 54, 24], vec![31, 34, 33, 32]])?;
+        let b = Permutation::try_create_permutation("b".to_string(), vec![vec![11, 22, 33, 44], vec![12, 23, 34, 41], vec![51, 54, 53, 52]])?;
 
         let fi = f.inverse();
         let li = l.inverse();
@@ -39,10 +45,10 @@ impl Cube2x2Definition {
 
         permitted_permutations.insert("id".to_string(), Permutation::identity());
         permitted_permutations.insert("f".to_string(), f);
-        permitted_permutations.insert("u".to_string(), l);
-        permitted_permutations.insert("r".to_string(), u);
-        permitted_permutations.insert("d".to_string(), r);
-        permitted_permutations.insert("l".to_string(), d);
+        permitted_permutations.insert("l".to_string(), l);
+        permitted_permutations.insert("u".to_string(), u);
+        permitted_permutations.insert("r".to_string(), r);
+        permitted_permutations.insert("d".to_string(), d);
         permitted_permutations.insert("b".to_string(), b);
 
         permitted_permutations.insert("fi".to_string(), fi);
@@ -61,9 +67,9 @@ impl Cube2x2Definition {
         permitted_permutations.insert("c_tech_left".to_string(), c_tech_left);
         permitted_permutations.insert("d_tech_left".to_string(), d_tech_left);
 
-        return Self {
+        return Ok(Self {
             permitted_permutations
-        };
+        });
     }
 }
 
@@ -90,6 +96,12 @@ impl PolyhedronDefinition for Cube2x2Definition {
             .expect(format!("unrecognized rotation name. {}", permutation_name).as_str());
     }
 
+    fn get_generators(&self) -> Vec<&Permutation> {
+        return ["f", "l", "u", "r", "d", "b"].iter()
+            .map(|name| self.get_permutation(name.to_string()))
+            .collect();
+    }
+
     fn print_polyhedron(&self,
                         applied_permutations: &Permutation,
                         before_state: &HashMap<usize, usize>,
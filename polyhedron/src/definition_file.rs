@@ -0,0 +1,398 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use colorful::{Color, Colorful};
+use serde::Deserialize;
+
+use permutations::Permutation;
+use permutations::error::PermutationError;
+
+use super::polyhedron::PolyhedronDefinition;
+
+/// A puzzle described by a TOML or JSON file instead of a Rust module:
+/// ```toml
+/// name = "Cube 2x2"
+/// layout = """
+/// {41} {42} {1} {2} ...
+/// """
+///
+/// [[faces]]
+/// name = "f"
+/// color = "red"
+/// facelets = [1, 2, 3, 4]
+///
+/// [[moves]]
+/// name = "f"
+/// cycles = [[1, 4, 3, 2], [13, 42, 31, 24], [14, 43, 32, 21]]
+///
+/// [[algorithms]]
+/// name = "sexy_move"
+/// moves = "r u ri ui"
+///
+/// [start_state]
+/// 1 = 2
+/// 2 = 1
+/// ```
+/// The moves are the base moves, their inverses being added with the suffix `i`, and `id` is the identity.
+/// Algorithms are sequences of moves or earlier algorithms separated by spaces.
+/// The layout is optional: it is printed with `{n}` replaced by the facelet at position n,
+/// otherwise the facelets are printed face by face. The start state, from positions to facelets,
+/// is optional as well and defaults to the solved state.
+pub struct FileDefinition {
+    name: String,
+    faces: Vec<Face>,
+    face_of_facelet: HashMap<usize, usize>,
+    permitted_permutations: HashMap<String, Permutation>,
+    generators: Vec<String>,
+    start_state: HashMap<usize, usize>,
+    layout: Option<String>,
+}
+
+struct Face {
+    name: String,
+    color: Color,
+    facelets: Vec<usize>,
+}
+
+/// Reasons why a definition file can't be loaded.
+#[derive(Debug)]
+pub enum DefinitionError {
+    /// The file can't be read.
+    Io(std::io::Error),
+    /// The file is not valid TOML or JSON, or misses a field.
+    Parse(String),
+    /// The extension of the file is neither `.toml`, `.json` nor `.def`.
+    UnknownFormat(String),
+    /// A color name is not one of the supported colors.
+    UnknownColor(String),
+    /// An algorithm uses a move that is not defined before it.
+    UnknownMove { name: String, algorithm: String },
+    /// A facelet is on two faces.
+    FaceletOnSeveralFaces(usize),
+    /// A move moves a point that is not a facelet.
+    NotAFacelet { facelet: usize, permutation: String },
+    /// The layout has a `{n}` where n is not a facelet.
+    LayoutNotAFacelet(usize),
+    /// The start state doesn't send the facelets onto the facelets.
+    InvalidStartState(String),
+    /// The cycles of a move don't describe a permutation.
+    Permutation { name: String, error: PermutationError },
+    /// A move or an algorithm has the name of another move, algorithm or generated inverse.
+    DuplicateName(String),
+    /// A line of a KSolve definition can't be read.
+    Syntax { line: usize, message: String },
+    /// The puzzle can't be written in the KSolve format.
+    NotExportable(String),
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            DefinitionError::Io(error) => write!(f, "{}", error),
+            DefinitionError::Parse(error) => write!(f, "{}", error),
+            DefinitionError::UnknownFormat(path) => write!(f, "unknown format for {}, expected .toml, .json or .def", path),
+            DefinitionError::UnknownColor(color) => write!(f, "unknown color {}", color),
+            DefinitionError::UnknownMove { name, algorithm } => write!(f, "unknown move {} in algorithm {}", name, algorithm),
+            DefinitionError::FaceletOnSeveralFaces(facelet) => write!(f, "facelet {} is on several faces", facelet),
+            DefinitionError::NotAFacelet { facelet, permutation } => write!(f, "move {} moves {}, which is not a facelet", permutation, facelet),
+            DefinitionError::LayoutNotAFacelet(position) => write!(f, "the layout shows {}, which is not a facelet", position),
+            DefinitionError::InvalidStartState(error) => write!(f, "invalid start state: {}", error),
+            DefinitionError::Permutation { name, error } => write!(f, "invalid move {}: {}", name, error),
+            DefinitionError::DuplicateName(name) => write!(f, "{} is defined several times", name),
+            DefinitionError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            DefinitionError::NotExportable(reason) => write!(f, "can't be exported: {}", reason),
+        };
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+#[derive(Deserialize)]
+pub(crate) struct DefinitionFile {
+    pub(crate) name: String,
+    pub(crate) faces: Vec<FaceFile>,
+    pub(crate) moves: Vec<MoveFile>,
+    #[serde(default)]
+    pub(crate) algorithms: Vec<AlgorithmFile>,
+    #[serde(default)]
+    pub(crate) start_state: BTreeMap<String, usize>,
+    pub(crate) layout: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct FaceFile {
+    pub(crate) name: String,
+    pub(crate) color: String,
+    pub(crate) facelets: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MoveFile {
+    pub(crate) name: String,
+    pub(crate) cycles: Vec<Vec<usize>>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AlgorithmFile {
+    name: String,
+    moves: String,
+}
+
+impl FileDefinition {
+    /// Loads a `.toml`, `.json` or `.def` definition file, panicking if it is invalid.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+
+        return Self::try_load(path).unwrap_or_else(|error| panic!("Invalid definition {}: {}", path.display(), error));
+    }
+
+    /// Same as `load`, but returns an error instead of panicking. A `.def` file is read in the KSolve format.
+    pub fn try_load<P: AsRef<Path>>(path: P) -> Result<Self, DefinitionError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(DefinitionError::Io)?;
+
+        return match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::try_from_toml(&text),
+            Some("json") => Self::try_from_json(&text),
+            Some("def") => crate::ksolve::from_ksolve(&text),
+            _ => Err(DefinitionError::UnknownFormat(path.display().to_string())),
+        };
+    }
+
+    pub fn try_from_toml(text: &str) -> Result<Self, DefinitionError> {
+        let file: DefinitionFile = toml::from_str(text).map_err(|error| DefinitionError::Parse(error.to_string()))?;
+
+        return Self::try_from_file(file);
+    }
+
+    pub fn try_from_json(text: &str) -> Result<Self, DefinitionError> {
+        let file: DefinitionFile = serde_json::from_str(text).map_err(|error| DefinitionError::Parse(error.to_string()))?;
+
+        return Self::try_from_file(file);
+    }
+
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    pub(crate) fn try_from_file(file: DefinitionFile) -> Result<Self, DefinitionError> {
+        let mut faces = Vec::new();
+        let mut face_of_facelet = HashMap::new();
+
+        for (i, face) in file.faces.into_iter().enumerate() {
+            for &facelet in &face.facelets {
+                if face_of_facelet.insert(facelet, i).is_some() {
+                    return Err(DefinitionError::FaceletOnSeveralFaces(facelet));
+                }
+            }
+
+            faces.push(Face { name: face.name, color: parse_color(&face.color)?, facelets: face.facelets });
+        }
+
+        let mut permitted_permutations = HashMap::new();
+        permitted_permutations.insert("id".to_string(), Permutation::identity());
+
+        let mut generators = Vec::new();
+        for base_move in file.moves {
+            let permutation = Permutation::try_create_permutation(base_move.name.clone(), base_move.cycles)
+                .map_err(|error| DefinitionError::Permutation { name: base_move.name.clone(), error })?;
+
+            if let Some(&facelet) = permutation.support().iter().find(|facelet| !face_of_facelet.contains_key(facelet)) {
+                return Err(DefinitionError::NotAFacelet { facelet, permutation: base_move.name });
+            }
+
+            // A move can't have the name of another move, of the inverse of a move, or be named like the inverse of a move
+            let inverse_name = format!("{}i", base_move.name);
+            for name in [&base_move.name, &inverse_name] {
+                if permitted_permutations.contains_key(name) {
+                    return Err(DefinitionError::DuplicateName(name.clone()));
+                }
+            }
+
+            permitted_permutations.insert(inverse_name, permutation.inverse());
+            permitted_permutations.insert(base_move.name.clone(), permutation);
+            generators.push(base_move.name);
+        }
+
+        for algorithm in file.algorithms {
+            let mut moves = Vec::new();
+            for name in algorithm.moves.split_whitespace() {
+                let permutation = permitted_permutations.get(name)
+                    .ok_or_else(|| DefinitionError::UnknownMove { name: name.to_string(), algorithm: algorithm.name.clone() })?;
+                moves.push(permutation);
+            }
+
+            if permitted_permutations.contains_key(&algorithm.name) {
+                return Err(DefinitionError::DuplicateName(algorithm.name));
+            }
+
+            let permutation = Permutation::create_permutation_from_composition(algorithm.name.clone(), moves);
+            permitted_permutations.insert(algorithm.name, permutation);
+        }
+
+        if let Some(layout) = &file.layout {
+            let mut unknown_position = None;
+            fill_layout(layout, |position| {
+                if !face_of_facelet.contains_key(&position) {
+                    unknown_position.get_or_insert(position);
+                }
+                String::new()
+            });
+
+            if let Some(position) = unknown_position {
+                return Err(DefinitionError::LayoutNotAFacelet(position));
+            }
+        }
+
+        let solved_state: HashMap<usize, usize> = face_of_facelet.keys().map(|&facelet| (facelet, facelet)).collect();
+        let mut start_state = solved_state.clone();
+        for (position, facelet) in file.start_state {
+            let position = position.parse::<usize>()
+                .map_err(|_| DefinitionError::InvalidStartState(format!("{} is not a position", position)))?;
+
+            if !solved_state.contains_key(&position) || !solved_state.contains_key(&facelet) {
+                return Err(DefinitionError::InvalidStartState(format!("{} or {} is not a facelet", position, facelet)));
+            }
+
+            start_state.insert(position, facelet);
+        }
+
+        let mut facelets: Vec<usize> = start_state.values().copied().collect();
+        facelets.sort_unstable();
+        facelets.dedup();
+        if facelets.len() != start_state.len() {
+            return Err(DefinitionError::InvalidStartState("a facelet is at several positions".to_string()));
+        }
+
+        return Ok(Self {
+            name: file.name,
+            faces,
+            face_of_facelet,
+            permitted_permutations,
+            generators,
+            start_state,
+            layout: file.layout,
+        });
+    }
+}
+
+impl PolyhedronDefinition for FileDefinition {
+    fn start_state(&self) -> HashMap<usize, usize> {
+        return self.start_state.clone();
+    }
+
+    fn solved_state(&self) -> HashMap<usize, usize> {
+        return self.face_of_facelet.keys().map(|&facelet| (facelet, facelet)).collect();
+    }
+
+    fn get_color(&self, face_name: String) -> Color {
+        return self.faces.iter()
+            .find(|face| face.name == face_name)
+            .map_or(Color::Grey0, |face| face.color);
+    }
+
+    fn get_face_name(&self, position: usize) -> String {
+        let face = self.face_of_facelet.get(&position)
+            .unwrap_or_else(|| panic!("Unrecognized position value : {}", position));
+
+        return self.faces[*face].name.clone();
+    }
+
+    fn print_polyhedron(&self,
+                        applied_permutations: &Permutation,
+                        before_state: &HashMap<usize, usize>,
+                        current_state: &HashMap<usize, usize>) {
+        let p = |position: usize| -> String {
+            let before_facelet = before_state.get(&position).unwrap();
+            let current_facelet = current_state.get(&position).unwrap();
+
+            let facelet_number_str = format!("{:02}", current_facelet);
+            let color = self.get_color(self.get_face_name(*current_facelet));
+
+            return if current_facelet == before_facelet {
+                // facelet didnt move
+                facelet_number_str.color(color).to_string()
+            } else {
+                facelet_number_str.color(Color::Black).bg_color(color).to_string()
+            };
+        };
+
+        println!();
+        println!("{}", self.name);
+        println!("Current permutation : {}", applied_permutations);
+
+        match &self.layout {
+            Some(layout) => println!("{}", fill_layout(layout, p)),
+            None => {
+                for face in &self.faces {
+                    let facelets: Vec<String> = face.facelets.iter().map(|&position| p(position)).collect();
+                    println!("{:>4} : {}", face.name, facelets.join(" "));
+                }
+            }
+        }
+
+        if self.solved_state() == *current_state && before_state != current_state {
+            println!("{}", "...................................".gradient(Color::Red));
+            println!("{}{}{}", "......".gradient(Color::Red),
+                     "      Solved      ".color(Color::Red).blink(),
+                     "...........".gradient(Color::Orange1));
+            println!("{}", "...................................".gradient(Color::Red));
+        }
+    }
+
+    fn get_permutation(&self, permutation_name: String) -> &Permutation {
+        return self.permitted_permutations.get(&permutation_name)
+            .unwrap_or_else(|| panic!("unrecognized rotation name. {}", permutation_name));
+    }
+
+    fn get_generators(&self) -> Vec<&Permutation> {
+        return self.generators.iter()
+            .map(|name| self.get_permutation(name.clone()))
+            .collect();
+    }
+}
+
+/// Replaces every `{n}` of the layout by the printed facelet at position n.
+fn fill_layout<F>(layout: &str, mut p: F) -> String
+    where F: FnMut(usize) -> String {
+    let mut filled = String::new();
+    let mut rest = layout;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+
+        let end = rest[start..].find('}').map(|end| start + end);
+        match end.and_then(|end| rest[(start + 1)..end].parse::<usize>().ok().map(|position| (end, position))) {
+            Some((end, position)) => {
+                filled.push_str(&p(position));
+                rest = &rest[(end + 1)..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[(start + 1)..];
+            }
+        }
+    }
+    filled.push_str(rest);
+
+    return filled;
+}
+
+fn parse_color(name: &str) -> Result<Color, DefinitionError> {
+    return match name.to_lowercase().as_str() {
+        "black" => Ok(Color::Black),
+        "red" => Ok(Color::Red),
+        "green" => Ok(Color::Green),
+        "yellow" => Ok(Color::Yellow),
+        "blue" => Ok(Color::Blue),
+        "magenta" => Ok(Color::Magenta),
+        "cyan" => Ok(Color::Cyan),
+        "white" => Ok(Color::White),
+        "grey" | "gray" => Ok(Color::Grey0),
+        "orange" => Ok(Color::Orange1),
+        "purple" => Ok(Color::Purple1a),
+        "pink" => Ok(Color::Pink1),
+        _ => Err(DefinitionError::UnknownColor(name.to_string())),
+    };
+}
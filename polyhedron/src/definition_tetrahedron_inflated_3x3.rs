@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use colorful::{Color, Colorful};
 
 use permutations::Permutation;
+use permutations::error::PermutationError;
 
 use super::polyhedron::PolyhedronDefinition;
 
@@ -12,12 +13,17 @@ pub struct TetrahedronInflated3x3Definition {
 
 impl TetrahedronInflated3x3Definition {
     pub fn new() -> Self {
+        return Self::try_new().unwrap_or_else(|error| panic!("Invalid definition of TetrahedronInflated3x3Definition: {}", error));
+    }
+
+    /// Same as `new`, but returns an error instead of panicking if a move is not a valid permutation.
+    pub fn try_new() -> Result<Self, PermutationError> {
         let mut permitted_permutations = HashMap::new();
 
-        let f = Permutation::create_permutation("f".to_string(), vec![vec![1, 6, 4], vec![2, 3, 5], vec![24, 34, 44], vec![26, 36, 46], vec![25, 35, 45]]);
-        let l = Permutation::create_permutation("l".to_string(), vec![vec![41, 46, 44], vec![42, 43, 45], vec![1, 36, 21], vec![2, 33, 22], vec![4, 31, 24]]);
-        let r = Permutation::create_permutation("r".to_string(), vec![vec![21, 26, 24], vec![22, 23, 25], vec![6, 46, 31], vec![3, 43, 32], vec![1, 41, 34]]);
-        let d = Permutation::create_permutation("d".to_string(), vec![vec![31, 36, 34], vec![32, 33, 35], vec![4, 26, 41], vec![5, 23, 42], vec![6, 21, 44]]);
+        let f = Permutation::try_create_permutation("f".to_string(), vec![vec![1, 6, 4], vec![2, 3, 5], vec![24, 34, 44], vec![26, 36, 46], vec![25, 35, 45]])?;
+        let l = Permutation::try_create_permutation("l".to_string(), vec![vec![41, 46, 44], vec![42, 43, 45], vec![1, 36, 21], vec![2, 33, 22], vec![4, 31, 24]])?;
+        let r = Permutation::try_create_permutation("r".to_string(), vec![vec![21, 26, 24], vec![22, 23, 25], vec![6, 46, 31], vec![3, 43, 32], vec![1, 41, 34]])?;
+        let d = Permutation::try_create_permutation("d".to_string(), vec![vec![31, 36, 34], vec![32, 33, 35], vec![4, 26, 41], vec![5, 23, 42], vec![6, 21, 44]])?;
 
         let fi = f.inverse();
         let li = l.inverse();
@@ -35,9 +41,9 @@ impl TetrahedronInflated3x3Definition {
         permitted_permutations.insert("ri".to_string(), ri);
         permitted_permutations.insert("di".to_string(), di);
 
-        return Self {
+        return Ok(Self {
             permitted_permutations
-        };
+        });
     }
 }
 
@@ -119,6 +125,12 @@ impl PolyhedronDefinition for TetrahedronInflated3x3Definition {
         return self.permitted_permutations.get(&permutation_name)
             .expect(format!("unrecognized rotation name. {}", permutation_name).as_str());
     }
+
+    fn get_generators(&self) -> Vec<&Permutation> {
+        return ["f", "l", "r", "d"].iter()
+            .map(|name| self.get_permutation(name.to_string()))
+            .collect();
+    }
 }
 
 fn solved_state() -> HashMap<usize, usize> {
@@ -0,0 +1,334 @@
+//! Conversion between the definitions and the KSolve `.def` format, also read by twsearch,
+//! in which other solvers describe their puzzles:
+//! ```text
+//! Name Cube 2x2
+//!
+//! Set ORBIT1 8 3
+//!
+//! Solved
+//! ORBIT1
+//! 1 2 3 4 5 6 7 8
+//! 0 0 0 0 0 0 0 0
+//! End
+//!
+//! Move f
+//! ORBIT1
+//! 4 1 2 3 5 6 7 8
+//! 0 2 1 0 0 0 0 0
+//! End
+//! ```
+//! A set is a kind of pieces, each having as many orientations as facelets. After a move, the piece
+//! at position i is the piece that was at the position given by the first line, 1 for the first position,
+//! its orientation being increased by the second line. A set that a move doesn't change can be left out.
+//!
+//! When exported, every orbit of facelets of at least two facelets is a set, its pieces being the pieces of
+//! `get_pieces`, and the moves are the base moves. When imported, the facelets are numbered from 1, set by set,
+//! piece by piece and orientation by orientation, every kind of piece of the solved state being a face,
+//! and the `StartState` block gives the start state. `Block`, which bandages pieces together, is not supported,
+//! and the blocks only restricting the search, like `Ignore` or `ForbiddenPairs`, are left out.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use permutations::block::smallest_blocks;
+use permutations::orbit::orbits;
+use permutations::Permutation;
+
+use crate::definition_file::{DefinitionError, DefinitionFile, FaceFile, FileDefinition, MoveFile};
+use crate::polyhedron::PolyhedronDefinition;
+
+const COLORS: [&str; 10] = ["red", "green", "yellow", "blue", "orange", "white", "magenta", "cyan", "purple", "pink"];
+
+/// Blocks of the format that only restrict the search or the scrambles, skipped until their `End`.
+const SKIPPED_BLOCKS: [&str; 5] = ["Ignore", "ForbiddenPairs", "ForbiddenGroups", "MoveLimits", "Scramble"];
+
+/// Writes the definition in the KSolve format, under the specified name.
+/// Returns an error if a move turns the facelets of a piece in a way that is not a rotation, like a mirror.
+pub fn to_ksolve<T: PolyhedronDefinition>(definition: &T, name: &str) -> Result<String, DefinitionError> {
+    let facelets = definition.solved_state();
+    let generators = definition.get_generators();
+
+    let sets: Vec<Vec<Vec<usize>>> = orbits(generators.clone()).into_iter()
+        .filter(|orbit| orbit.len() > 1 && facelets.contains_key(&orbit[0]))
+        .map(|orbit| oriented_pieces(smallest_blocks(orbit[0], generators.clone()), &generators))
+        .collect();
+
+    let set_name = |i: usize| format!("ORBIT{}", i + 1);
+    let line = |numbers: Vec<usize>| numbers.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(" ");
+
+    let mut text = format!("Name {}\n\n", name);
+    for (i, pieces) in sets.iter().enumerate() {
+        text.push_str(&format!("Set {} {} {}\n", set_name(i), pieces.len(), pieces[0].len()));
+    }
+
+    text.push_str("\nSolved\n");
+    for (i, pieces) in sets.iter().enumerate() {
+        text.push_str(&format!("{}\n{}\n{}\n", set_name(i), line((1..=pieces.len()).collect()), line(vec![0; pieces.len()])));
+    }
+    text.push_str("End\n");
+
+    for (g_index, g) in generators.iter().enumerate() {
+        text.push_str(&format!("\nMove {}\n", g.name().unwrap_or_else(|| format!("g{}", g_index))));
+
+        for (i, pieces) in sets.iter().enumerate() {
+            let (permutation, orientations) = piece_move(pieces, g)?;
+
+            if permutation.iter().enumerate().any(|(position, &from)| from != position + 1) || orientations.iter().any(|&o| o != 0) {
+                text.push_str(&format!("{}\n{}\n{}\n", set_name(i), line(permutation), line(orientations)));
+            }
+        }
+        text.push_str("End\n");
+    }
+
+    return Ok(text);
+}
+
+/// Reads a definition in the KSolve format. The base moves are the moves of the file, their inverses having the suffix `i`.
+pub fn from_ksolve(text: &str) -> Result<FileDefinition, DefinitionError> {
+    let mut lines = text.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap().split_whitespace().collect::<Vec<&str>>()))
+        .filter(|(_, tokens)| !tokens.is_empty())
+        .peekable();
+
+    let mut name = String::new();
+    let mut sets: Vec<KSolveSet> = Vec::new();
+    let mut solved: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut moves: Vec<(String, SetBlock)> = Vec::new();
+    let mut start: Option<(usize, SetBlock)> = None;
+
+    while let Some((number, tokens)) = lines.next() {
+        match tokens[0] {
+            "Name" => name = tokens[1..].join(" "),
+            "Set" => {
+                if tokens.len() != 4 {
+                    return Err(syntax_error(number, "expected Set <name> <pieces> <orientations>"));
+                }
+                let set = KSolveSet {
+                    name: tokens[1].to_string(),
+                    size: parse_number(number, tokens[2])?,
+                    orientations: parse_number(number, tokens[3])?,
+                };
+                if set.size == 0 || set.orientations == 0 {
+                    return Err(syntax_error(number, "a set has at least one piece and one orientation"));
+                }
+                sets.push(set);
+            }
+            "Solved" => {
+                for (set_name, (permutation, _)) in read_set_block(&mut lines, &sets, number)? {
+                    solved.insert(set_name, permutation);
+                }
+            }
+            "StartState" => start = Some((number, read_set_block(&mut lines, &sets, number)?)),
+            "Block" => return Err(syntax_error(number, "Block is not supported, pieces can't be bandaged together")),
+            "Move" => {
+                let move_name = tokens.get(1).ok_or_else(|| syntax_error(number, "expected Move <name>"))?.to_string();
+                moves.push((move_name, read_set_block(&mut lines, &sets, number)?));
+            }
+            keyword if SKIPPED_BLOCKS.contains(&keyword) => {
+                if !lines.by_ref().any(|(_, tokens)| tokens[0] == "End") {
+                    return Err(syntax_error(number, &format!("{} without End", keyword)));
+                }
+            }
+            keyword => return Err(syntax_error(number, &format!("unknown keyword {}", keyword))),
+        }
+    }
+
+    // The facelet of the orientation o of the piece p of a set
+    let mut offsets = Vec::new();
+    let mut degree = 0;
+    for set in &sets {
+        offsets.push(degree);
+        degree += set.size * set.orientations;
+    }
+    let facelet = |s: usize, p: usize, o: usize| offsets[s] + p * sets[s].orientations + o + 1;
+
+    let kinds: Vec<Vec<usize>> = sets.iter()
+        .map(|set| solved.get(&set.name).cloned().unwrap_or_else(|| (1..=set.size).collect()))
+        .collect();
+
+    let mut faces = Vec::new();
+    for (s, set) in sets.iter().enumerate() {
+        let kinds = &kinds[s];
+
+        let mut labels: Vec<usize> = kinds.clone();
+        labels.sort_unstable();
+        labels.dedup();
+
+        for label in labels {
+            let facelets = (0..set.size)
+                .filter(|&p| kinds[p] == label)
+                .flat_map(|p| (0..set.orientations).map(move |o| (p, o)))
+                .map(|(p, o)| facelet(s, p, o))
+                .collect();
+
+            faces.push(FaceFile {
+                name: format!("{}_{}", set.name, label),
+                color: COLORS[(label - 1) % COLORS.len()].to_string(),
+                facelets,
+            });
+        }
+    }
+
+    let mut move_files = Vec::new();
+    for (move_name, changes) in moves {
+        let mut one_line: Vec<usize> = (0..=degree).collect();
+
+        for (s, set) in sets.iter().enumerate() {
+            if let Some((permutation, orientations)) = changes.get(&set.name) {
+                for p in 0..set.size {
+                    for o in 0..set.orientations {
+                        let turned = (o + set.orientations - orientations[p]) % set.orientations;
+                        one_line[facelet(s, p, o)] = facelet(s, permutation[p] - 1, turned);
+                    }
+                }
+            }
+        }
+
+        let cycles = Permutation::try_from_one_line(one_line)
+            .map_err(|error| DefinitionError::Permutation { name: move_name.clone(), error })?
+            .cycles();
+        move_files.push(MoveFile { name: move_name, cycles });
+    }
+
+    // The start state gives the kinds of the pieces, a piece of the solved state of the same kind being taken for each
+    let mut start_state = BTreeMap::new();
+    if let Some((number, changes)) = start {
+        for (s, set) in sets.iter().enumerate() {
+            let Some((pieces, orientations)) = changes.get(&set.name) else {
+                continue;
+            };
+
+            let mut unused: Vec<usize> = (0..set.size).collect();
+            for p in 0..set.size {
+                let index = unused.iter().position(|&q| kinds[s][q] == pieces[p])
+                    .ok_or_else(|| syntax_error(number, &format!("the start state of {} doesn't have the pieces of the solved state", set.name)))?;
+                let q = unused.remove(index);
+
+                for o in 0..set.orientations {
+                    let turned = (o + set.orientations - orientations[p]) % set.orientations;
+                    start_state.insert(facelet(s, p, o).to_string(), facelet(s, q, turned));
+                }
+            }
+        }
+    }
+
+    return FileDefinition::try_from_file(DefinitionFile {
+        name,
+        faces,
+        moves: move_files,
+        algorithms: Vec::new(),
+        start_state,
+        layout: None,
+    });
+}
+
+/// The lines of a Solved or Move block by set: the positions the pieces come from and their orientations.
+type SetBlock = HashMap<String, (Vec<usize>, Vec<usize>)>;
+
+struct KSolveSet {
+    name: String,
+    size: usize,
+    orientations: usize,
+}
+
+/// Orders the facelets of every piece so that the moves turn them by rotations: a move sending the piece
+/// at position j to the position i sends the k-th facelet of j to the (k + t)-th facelet of i for some twist t.
+/// The first piece keeps its order, the others take the order of their image through the moves.
+fn oriented_pieces(pieces: Vec<Vec<usize>>, generators: &[&Permutation]) -> Vec<Vec<usize>> {
+    let piece_of: HashMap<usize, usize> = pieces.iter().enumerate()
+        .flat_map(|(i, piece)| piece.iter().map(move |&facelet| (facelet, i)))
+        .collect();
+
+    let mut oriented: Vec<Option<Vec<usize>>> = vec![None; pieces.len()];
+    oriented[0] = Some(pieces[0].clone());
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(i) = queue.pop_front() {
+        for g in generators {
+            let image: Vec<usize> = oriented[i].as_ref().unwrap().iter().map(|&facelet| g.apply(facelet)).collect();
+            let j = piece_of[&image[0]];
+
+            if oriented[j].is_none() {
+                oriented[j] = Some(image);
+                queue.push_back(j);
+            }
+        }
+    }
+
+    return oriented.into_iter().map(|piece| piece.unwrap()).collect();
+}
+
+/// Returns the KSolve move of a set: for every position, the position of the piece coming to it and its twist.
+fn piece_move(pieces: &[Vec<usize>], g: &Permutation) -> Result<(Vec<usize>, Vec<usize>), DefinitionError> {
+    let orientations = pieces[0].len();
+    let mut permutation = Vec::with_capacity(pieces.len());
+    let mut twists = Vec::with_capacity(pieces.len());
+
+    for piece in pieces {
+        // The facelets of the position i are taken from the facelets of the position j
+        let image: Vec<usize> = piece.iter().map(|&facelet| g.apply(facelet)).collect();
+        let j = pieces.iter().position(|other| other.contains(&image[0])).unwrap();
+        let shift = pieces[j].iter().position(|&facelet| facelet == image[0]).unwrap();
+
+        if (0..orientations).any(|k| image[k] != pieces[j][(k + shift) % orientations]) {
+            return Err(DefinitionError::NotExportable(format!("{} doesn't turn the piece {:?} by a rotation", g, piece)));
+        }
+
+        permutation.push(j + 1);
+        twists.push((orientations - shift) % orientations);
+    }
+
+    return Ok((permutation, twists));
+}
+
+/// Reads the lines of a Solved or Move block up to its End: for every set, the positions the pieces come from
+/// and, optionally, their orientations.
+fn read_set_block<'a, I>(lines: &mut std::iter::Peekable<I>, sets: &[KSolveSet], start: usize)
+                         -> Result<SetBlock, DefinitionError>
+    where I: Iterator<Item=(usize, Vec<&'a str>)> {
+    let mut block = HashMap::new();
+
+    loop {
+        let (number, tokens) = lines.next().ok_or_else(|| syntax_error(start, "block without End"))?;
+        if tokens[0] == "End" {
+            return Ok(block);
+        }
+
+        let set = sets.iter().find(|set| set.name == tokens[0])
+            .ok_or_else(|| syntax_error(number, &format!("unknown set {}", tokens[0])))?;
+
+        let (number, tokens) = lines.next().ok_or_else(|| syntax_error(number, "missing permutation"))?;
+        let permutation = parse_numbers(number, &tokens, set.size)?;
+        if permutation.iter().any(|&p| p == 0 || p > set.size) {
+            return Err(syntax_error(number, &format!("positions of {} go from 1 to {}", set.name, set.size)));
+        }
+
+        let mut orientations = vec![0; set.size];
+        if let Some((number, tokens)) = lines.peek() {
+            if tokens[0].parse::<usize>().is_ok() {
+                orientations = parse_numbers(*number, tokens, set.size)?;
+                if orientations.iter().any(|&o| o >= set.orientations) {
+                    return Err(syntax_error(*number, &format!("orientations of {} go from 0 to {}", set.name, set.orientations - 1)));
+                }
+                lines.next();
+            }
+        }
+
+        block.insert(set.name.clone(), (permutation, orientations));
+    }
+}
+
+fn parse_numbers(line: usize, tokens: &[&str], count: usize) -> Result<Vec<usize>, DefinitionError> {
+    if tokens.len() != count {
+        return Err(syntax_error(line, &format!("expected {} numbers", count)));
+    }
+
+    return tokens.iter().map(|token| parse_number(line, token)).collect();
+}
+
+fn parse_number(line: usize, token: &str) -> Result<usize, DefinitionError> {
+    return token.parse::<usize>().map_err(|_| syntax_error(line, &format!("{} is not a number", token)));
+}
+
+fn syntax_error(line: usize, message: &str) -> DefinitionError {
+    return DefinitionError::Syntax { line, message: message.to_string() };
+}
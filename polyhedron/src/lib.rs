@@ -1,11 +1,21 @@
 use std::collections::{BTreeMap, HashMap};
 use colorful::Color;
 use permutations::Permutation;
+use permutations::group::PermutationGroup;
+use permutations::notation::ParseWordError;
+use permutations::orbit::orbits;
+use permutations::word::Word;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use crate::polyhedron::PolyhedronDefinition;
+use crate::scramble::{Scramble, ScrambleKind};
 
 pub mod polyhedron;
 pub mod definition_cube_2x2;
+pub mod definition_file;
 pub mod definition_tetrahedron_inflated_3x3;
+pub mod ksolve;
+pub mod scramble;
 
 pub type Pos3d = [f32; 3];
 pub type Quad = [Pos3d; 4];
@@ -17,6 +27,13 @@ pub struct Polyhedron<T: PolyhedronDefinition> {
     current_state: HashMap<usize, usize>,
     before_state: HashMap<usize, usize>,
 
+    // Moves played from the start state, the ones after history_position having been undone
+    history: Vec<Permutation>,
+    history_position: usize,
+    checkpoints: BTreeMap<String, Vec<Permutation>>,
+
+    // Composition of the moves of the history up to history_position, kept without a word so that
+    // applying a move doesn't copy the words of all the moves before it
     applied_permutations: Permutation,
 }
 
@@ -29,15 +46,72 @@ impl<T: PolyhedronDefinition> Polyhedron<T> {
             polyhedron_definition: poly_definition,
             solved_state: solved_state.clone(),
             before_state: start_state.clone(),
-            applied_permutations: Permutation::identity(),
+            history: Vec::new(),
+            history_position: 0,
+            checkpoints: BTreeMap::new(),
+            applied_permutations: Permutation::from_one_line(vec![]),
             current_state: start_state.clone(),
             start_state,
         }
     }
 
     pub fn rotate(&mut self, perm: &str) {
-        let permutation = self.polyhedron_definition.get_permutation(perm.to_string());
+        let permutation = self.polyhedron_definition.get_permutation(perm.to_string()).clone();
+
+        self.play(permutation);
+    }
+
+    pub fn rotate_many(&mut self, perms: Vec<&str>) {
+        for perm in perms {
+            self.rotate(perm);
+        }
+    }
+
+    /// Applies moves written in the usual notation, e.g. `R U R' U2 [F: R U R']`, one base move after the other.
+    /// Nothing is applied if the text can't be parsed, the error giving the part of the text at fault.
+    pub fn rotate_notation(&mut self, text: &str) -> Result<(), ParseWordError> {
+        let word = self.polyhedron_definition.parse_moves(text)?;
+        self.play_word(&word);
+
+        return Ok(());
+    }
+
+    /// Resets the polyhedron and scrambles it, with the same moves for the same seed.
+    /// Returns the moves played, named after the base moves, and the state reached.
+    pub fn scramble(&mut self, kind: ScrambleKind, seed: u64) -> Scramble {
+        let generators = self.polyhedron_definition.get_generators();
+        let moves = match kind {
+            ScrambleKind::RandomMoves(length) => scramble::random_moves(generators, length, seed),
+            ScrambleKind::RandomState => scramble::random_state(generators, seed),
+        };
+
+        self.reset();
+        self.play_word(&moves);
+
+        return Scramble::create_scramble(moves, self.current_state.clone());
+    }
+
+    /// Plays the base moves of the word one after the other.
+    fn play_word(&mut self, word: &Word) {
+        for (name, k) in word.letters() {
+            let permutation = self.polyhedron_definition.get_generators().into_iter()
+                .find(|g| g.name().as_deref() == Some(name.as_str()))
+                .unwrap()
+                .pow(k);
 
+            self.play(permutation);
+        }
+    }
+
+    /// Applies the move and adds it to the history, forgetting the moves that were undone.
+    fn play(&mut self, permutation: Permutation) {
+        self.history.truncate(self.history_position);
+        self.apply_permutation(&permutation);
+        self.history.push(permutation);
+        self.history_position += 1;
+    }
+
+    fn apply_permutation(&mut self, permutation: &Permutation) {
         self.applied_permutations = self.applied_permutations.compose(permutation);
 
         self.before_state = self.current_state.clone();
@@ -50,16 +124,119 @@ impl<T: PolyhedronDefinition> Polyhedron<T> {
         }
     }
 
-    pub fn rotate_many(&mut self, perms: Vec<&str>) {
-        for perm in perms {
-            self.rotate(perm);
-        }
-    }
-
+    /// Goes back to the start state and clears the history. The checkpoints are kept.
     pub fn reset(&mut self) {
         self.before_state = self.start_state.clone();
         self.current_state = self.start_state.clone();
-        self.applied_permutations = Permutation::identity();
+        self.applied_permutations = Permutation::from_one_line(vec![]);
+        self.history.clear();
+        self.history_position = 0;
+    }
+
+    /// Resets the polyhedron to a state drawn uniformly among the states reachable from the start state.
+    /// The same seed always gives the same state. The history is the random element as a single move.
+    pub fn reset_random(&mut self, seed: u64) {
+        let group = PermutationGroup::create_group(self.polyhedron_definition.get_generators());
+        let permutation = group.random_element(&mut StdRng::seed_from_u64(seed));
+
+        self.reset();
+        self.play(permutation);
+    }
+
+    /// Returns the moves played since the start state, including the ones undone that can be redone.
+    pub fn history(&self) -> &Vec<Permutation> {
+        return &self.history;
+    }
+
+    /// Returns the number of moves of the history that are applied, the moves after it having been undone.
+    pub fn history_position(&self) -> usize {
+        return self.history_position;
+    }
+
+    /// Undoes the last applied move. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        return self.history_position > 0 && self.go_to(self.history_position - 1);
+    }
+
+    /// Applies again the last undone move. Returns false if there is none.
+    pub fn redo(&mut self) -> bool {
+        return self.go_to(self.history_position + 1);
+    }
+
+    /// Goes to the state after the first index moves of the history, 0 being the start state, undoing or redoing moves.
+    /// Returns false if the history is shorter than index.
+    pub fn go_to(&mut self, index: usize) -> bool {
+        if index > self.history.len() {
+            return false;
+        }
+
+        // Only the moves between the current position and index are redone or undone
+        self.applied_permutations = match index >= self.history_position {
+            true => self.history[self.history_position..index].iter()
+                .fold(self.applied_permutations.clone(), |applied, permutation| applied.compose(permutation)),
+            false => self.history[index..self.history_position].iter().rev()
+                .fold(self.applied_permutations.clone(), |applied, permutation| applied.compose(&permutation.inverse())),
+        };
+        self.history_position = index;
+
+        self.before_state = self.current_state.clone();
+        for key in self.solved_state.keys() {
+            let new_state = self.start_state.get(&self.applied_permutations.apply(*key)).unwrap();
+            self.current_state.insert(*key, *new_state);
+        }
+
+        return true;
+    }
+
+    /// Saves the applied moves under the name, replacing the checkpoint of the same name.
+    pub fn save_checkpoint(&mut self, name: &str) {
+        self.checkpoints.insert(name.to_string(), self.history[..self.history_position].to_vec());
+    }
+
+    /// Goes back to the state of the checkpoint, its moves becoming the history. Returns false if there is no such checkpoint.
+    pub fn restore_checkpoint(&mut self, name: &str) -> bool {
+        return match self.checkpoints.get(name) {
+            Some(moves) => {
+                self.history = moves.clone();
+                self.history_position = 0;
+                self.applied_permutations = Permutation::from_one_line(vec![]);
+                self.go_to(self.history.len())
+            }
+            None => false,
+        };
+    }
+
+    /// Returns the names of the checkpoints, sorted.
+    pub fn checkpoint_names(&self) -> Vec<String> {
+        return self.checkpoints.keys().cloned().collect();
+    }
+
+    /// Returns the partition of the facelets into orbits under the moves: a facelet can only be moved
+    /// to the positions of its orbit, e.g. corner facelets never go to edge positions.
+    pub fn facelet_orbits(&self) -> Vec<Vec<usize>> {
+        return orbits(self.polyhedron_definition.get_generators()).into_iter()
+            .filter(|orbit| self.solved_state.contains_key(&orbit[0]))
+            .collect();
+    }
+
+    /// Returns a sequence of moves bringing the current state back to the solved state,
+    /// or None if the solved state cannot be reached from the current one. The sequence is not necessarily short.
+    pub fn find_solution(&self) -> Option<Vec<String>> {
+        let group = PermutationGroup::create_group(self.polyhedron_definition.get_generators());
+
+        // The moves w to apply must satisfy current_state[w(k)] = solved_state[k] for every position k
+        let mut position_of_facelet = HashMap::new();
+        for (position, facelet) in &self.current_state {
+            position_of_facelet.insert(*facelet, *position);
+        }
+
+        let degree = self.solved_state.keys().max().map_or(0, |max| max + 1);
+        let mut one_line = Vec::from_iter(0..degree);
+        for (position, facelet) in &self.solved_state {
+            one_line[*position] = *position_of_facelet.get(facelet)?;
+        }
+
+        return group.factorize(&Permutation::create_permutation_one_line("solution".to_string(), one_line));
     }
 
     pub fn print_polyhedron(&self) {
@@ -124,6 +301,10 @@ impl<T: PolyhedronDefinition> Polyhedron<T> {
 mod tests {
     use super::*;
     use definition_cube_2x2::Cube2x2Definition;
+    use definition_file::{DefinitionError, FileDefinition};
+    use definition_tetrahedron_inflated_3x3::TetrahedronInflated3x3Definition;
+    use permutations::BigUint;
+    use permutations::group::PermutationGroup;
 
     #[test]
     fn it_works() {
@@ -134,4 +315,400 @@ mod tests {
         cube2x2.rotate("b_tech_right");
         cube2x2.print_polyhedron();
     }
+
+    #[test]
+    fn factorize_algorithms() {
+        let cube_2x2 = Cube2x2Definition::new();
+        let group = PermutationGroup::create_group(cube_2x2.get_generators());
+
+        for algorithm in ["a_tech_right", "b_tech_right", "a_left_left", "b_tech_left", "c_tech_right", "d_tech_right"] {
+            let names = group.factorize(cube_2x2.get_permutation(algorithm.to_string())).unwrap();
+
+            let mut from_names = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+            from_names.rotate_many(names.iter().map(|name| name.as_str()).collect());
+
+            let mut from_algorithm = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+            from_algorithm.rotate(algorithm);
+
+            assert_eq!(from_names.current_state, from_algorithm.current_state);
+        }
+    }
+
+    #[test]
+    fn find_solution() {
+        let mut cube2x2 = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        cube2x2.rotate_many(vec!["f", "r", "ui", "a_tech_right", "l", "b", "bi", "di"]);
+
+        let solution = cube2x2.find_solution().unwrap();
+        cube2x2.rotate_many(solution.iter().map(|name| name.as_str()).collect());
+        assert_eq!(cube2x2.current_state, cube2x2.solved_state);
+
+        let mut tetrahedron = Polyhedron::create_polyhedron(TetrahedronInflated3x3Definition::new());
+        tetrahedron.rotate_many(vec!["f", "r", "li", "d", "d"]);
+
+        let solution = tetrahedron.find_solution().unwrap();
+        tetrahedron.rotate_many(solution.iter().map(|name| name.as_str()).collect());
+        assert_eq!(tetrahedron.current_state, tetrahedron.solved_state);
+    }
+
+    #[test]
+    fn reset_random() {
+        let mut cube2x2 = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        cube2x2.reset_random(3);
+        assert_ne!(cube2x2.current_state, cube2x2.start_state);
+
+        let mut same_seed = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        same_seed.rotate("f");
+        same_seed.reset_random(3);
+        assert_eq!(same_seed.current_state, cube2x2.current_state);
+
+        same_seed.reset_random(4);
+        assert_ne!(same_seed.current_state, cube2x2.current_state);
+
+        let solution = cube2x2.find_solution().unwrap();
+        cube2x2.rotate_many(solution.iter().map(|name| name.as_str()).collect());
+        assert_eq!(cube2x2.current_state, cube2x2.solved_state);
+    }
+
+    #[test]
+    fn rotate_notation() {
+        let mut from_notation = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        from_notation.rotate_notation("R U R' U2 (R U)3 [R, U] [F: R U R'] // comment").unwrap();
+
+        let mut from_names = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        from_names.rotate_many(vec!["r", "u", "ri", "u", "u", "r", "u", "r", "u", "r", "u", "r", "u", "ri", "ui", "f", "r", "u", "ri", "fi"]);
+        assert_eq!(from_notation.current_state, from_names.current_state);
+        assert_eq!(from_notation.applied_permutations, from_names.applied_permutations);
+
+        let mut tetrahedron = Polyhedron::create_polyhedron(TetrahedronInflated3x3Definition::new());
+        tetrahedron.rotate_notation("(F L)45 [D, R]6").unwrap();
+        assert_eq!(tetrahedron.current_state, tetrahedron.solved_state);
+
+        let error = tetrahedron.rotate_notation("F L U'").unwrap_err();
+        assert_eq!(error, ParseWordError::UnknownMove { span: permutations::notation::Span { start: 4, end: 5 }, name: "U".to_string() });
+        assert_eq!(tetrahedron.current_state, tetrahedron.solved_state);
+        assert!(matches!(tetrahedron.rotate_notation("(R L)1000000000000"), Err(ParseWordError::InvalidNumber { .. })));
+    }
+
+    #[test]
+    fn history() {
+        let mut cube2x2 = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        cube2x2.rotate_many(vec!["f", "r"]);
+        let after_f_r = cube2x2.current_state.clone();
+        cube2x2.rotate("ui");
+        cube2x2.save_checkpoint("three moves");
+
+        assert!(cube2x2.undo());
+        assert_eq!(cube2x2.current_state, after_f_r);
+        let cube_2x2 = Cube2x2Definition::new();
+        assert_eq!(cube2x2.applied_permutations, cube_2x2.get_permutation("f".to_string()).compose(cube_2x2.get_permutation("r".to_string())));
+        assert_eq!(cube2x2.applied_permutations.word(), None);
+        assert!(cube2x2.undo() && cube2x2.undo() && !cube2x2.undo());
+        assert_eq!(cube2x2.current_state, cube2x2.start_state);
+
+        assert!(cube2x2.go_to(2));
+        assert_eq!(cube2x2.current_state, after_f_r);
+        assert!(cube2x2.redo() && !cube2x2.redo() && !cube2x2.go_to(4));
+        assert_eq!(cube2x2.history_position(), 3);
+
+        // A move after an undo replaces the undone moves
+        cube2x2.go_to(1);
+        cube2x2.rotate("d");
+        let names: Vec<String> = cube2x2.history().iter().map(|permutation| permutation.name().unwrap()).collect();
+        assert_eq!(names, vec!["f", "d"]);
+        assert!(!cube2x2.redo());
+
+        cube2x2.reset();
+        assert!(cube2x2.history().is_empty());
+        assert!(cube2x2.restore_checkpoint("three moves") && !cube2x2.restore_checkpoint("four moves"));
+        assert!(cube2x2.undo());
+        assert_eq!(cube2x2.current_state, after_f_r);
+        assert_eq!(cube2x2.checkpoint_names(), vec!["three moves".to_string()]);
+
+        cube2x2.reset_random(3);
+        assert_eq!(cube2x2.history().len(), 1);
+        assert!(cube2x2.undo());
+        assert_eq!(cube2x2.current_state, cube2x2.start_state);
+    }
+
+    #[test]
+    fn scramble() {
+        let mut cube2x2 = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        let scramble = cube2x2.scramble(ScrambleKind::RandomMoves(25), 7);
+        let letters = scramble.moves().letters();
+
+        assert_eq!(letters.len(), 25);
+        assert_eq!(cube2x2.current_state, *scramble.state());
+        assert!(letters.iter().all(|(_, k)| [-1, 1, 2].contains(k)));
+        assert!(letters.windows(2).all(|pair| pair[0].0 != pair[1].0));
+
+        // f b f would merge into f2 b
+        let opposite = |a: &str, b: &str| ["fb", "lr", "ud"].iter().any(|axis| axis.contains(a) && axis.contains(b));
+        assert!(letters.windows(3).all(|moves| !(moves[0].0 == moves[2].0 && opposite(&moves[0].0, &moves[1].0))));
+
+        // Same seed, same scramble, which the notation parser reads back
+        let mut again = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        assert_eq!(again.scramble(ScrambleKind::RandomMoves(25), 7).moves(), scramble.moves());
+        again.reset();
+        again.rotate_notation(&scramble.moves().to_string()).unwrap();
+        assert_eq!(again.current_state, cube2x2.current_state);
+        assert_ne!(again.scramble(ScrambleKind::RandomMoves(25), 8).moves(), scramble.moves());
+
+        // Moves that all commute can each be turned only once
+        let a = Permutation::from_cycles(vec![vec![1, 2]]);
+        let b = Permutation::from_cycles(vec![vec![3, 4, 5]]);
+        let c = Permutation::from_cycles(vec![vec![6, 7, 8, 9]]);
+        for seed in 0..10 {
+            let mut names: Vec<String> = scramble::random_moves(vec![&a, &b, &c], 10, seed).letters().into_iter().map(|(name, _)| name).collect();
+            names.sort();
+            assert_eq!(names, vec!["g0", "g1", "g2"]);
+        }
+
+        // d commutes with c but not with a nor b
+        let d = Permutation::from_cycles(vec![vec![2, 3]]);
+        let letters = scramble::random_moves(vec![&a, &b, &c, &d], 200, 0).letters();
+        assert_eq!(letters.len(), 200);
+
+        let mut tetrahedron = Polyhedron::create_polyhedron(TetrahedronInflated3x3Definition::new());
+        let scramble = tetrahedron.scramble(ScrambleKind::RandomState, 3);
+        assert_eq!(tetrahedron.current_state, *scramble.state());
+        assert_eq!(tetrahedron.history().len(), scramble.moves().move_count());
+        assert_eq!(tetrahedron.scramble(ScrambleKind::RandomState, 3).moves(), scramble.moves());
+
+        let solution = tetrahedron.find_solution().unwrap();
+        tetrahedron.rotate_many(solution.iter().map(|name| name.as_str()).collect());
+        assert_eq!(tetrahedron.current_state, tetrahedron.solved_state);
+    }
+
+    #[test]
+    fn facelet_orbits() {
+        let cube2x2 = Polyhedron::create_polyhedron(Cube2x2Definition::new());
+        let orbits = cube2x2.facelet_orbits();
+        assert_eq!(orbits.len(), 1);
+        assert_eq!(orbits[0].len(), 24);
+
+        let tetrahedron = Polyhedron::create_polyhedron(TetrahedronInflated3x3Definition::new());
+        let orbits = tetrahedron.facelet_orbits();
+
+        // Corner facelets and edge facelets of the four faces
+        assert_eq!(orbits, vec![vec![1, 4, 6, 21, 24, 26, 31, 34, 36, 41, 44, 46], vec![2, 3, 5, 22, 23, 25, 32, 33, 35, 42, 43, 45]]);
+    }
+
+    #[test]
+    fn pieces() {
+        let pieces = Cube2x2Definition::new().get_pieces();
+        assert_eq!(pieces.len(), 8);
+        assert!(pieces.contains(&vec![1, 14, 42]));
+        assert!(pieces.iter().all(|piece| piece.len() == 3));
+
+        // 4 corners and 6 edges
+        let pieces = TetrahedronInflated3x3Definition::new().get_pieces();
+        assert_eq!(pieces, vec![
+            vec![1, 24, 46], vec![4, 36, 44], vec![6, 26, 34], vec![21, 31, 41],
+            vec![2, 45], vec![3, 25], vec![5, 35], vec![22, 43], vec![23, 32], vec![33, 42],
+        ]);
+    }
+
+    #[test]
+    fn piece_action() {
+        let cube_2x2 = Cube2x2Definition::new();
+        let group = PermutationGroup::create_group(cube_2x2.get_generators());
+        let corners = cube_2x2.piece_action();
+
+        assert_eq!(corners.apply(cube_2x2.get_permutation("f".to_string())).cycle_type(), vec![4]);
+        assert_eq!(corners.image(&group).order(), BigUint::from(40_320u32));
+        assert_eq!(corners.kernel(&group).order(), BigUint::from(2_187u32));
+
+        // Corners and edges are numbered together, the edges after the 4 corners: f turns 3 of each
+        let tetrahedron = TetrahedronInflated3x3Definition::new();
+        let group = PermutationGroup::create_group(tetrahedron.get_generators());
+        let pieces = tetrahedron.piece_action();
+
+        let image = pieces.apply(tetrahedron.get_permutation("f".to_string()));
+        assert_eq!(image.cycles(), vec![vec![0, 2, 1], vec![4, 5, 6]]);
+        assert_eq!(pieces.image(&group).order(), BigUint::from(12u32 * 360));
+        assert_eq!(pieces.kernel(&group).order(), BigUint::from(3u32.pow(3) * 2u32.pow(5)));
+    }
+
+    #[test]
+    fn find_relations() {
+        let tetrahedron = TetrahedronInflated3x3Definition::new();
+        let relations = tetrahedron.find_relations(2);
+        let relators: Vec<String> = relations.presentation().relators().iter().map(|r| r.to_string()).collect();
+
+        assert!(relations.presentation().is_satisfied_by(tetrahedron.get_generators()));
+        assert!(relators.contains(&"f3".to_string()) && relators.contains(&"(f l)45".to_string()));
+        assert!(relations.equal_generators().is_empty() && relations.inverse_generators().is_empty());
+
+        // An inverse move given as a base move is reported, named after its word
+        let cube_2x2 = Cube2x2Definition::new();
+        let moves = vec![cube_2x2.get_permutation("f".to_string()), cube_2x2.get_permutation("fi".to_string())];
+        let relations = permutations::relations::find_relations(moves, 2, 100);
+        assert_eq!(relations.inverse_generators(), &vec![("f".to_string(), "f'".to_string())]);
+    }
+
+    #[test]
+    fn file_definitions() {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/definitions");
+
+        let cube_2x2 = FileDefinition::load(format!("{}/cube_2x2.toml", directory));
+        let expected = Cube2x2Definition::new();
+        assert_eq!(cube_2x2.name(), "Cube 2x2");
+        assert_eq!(cube_2x2.get_generators(), expected.get_generators());
+        assert_eq!(cube_2x2.solved_state(), expected.solved_state());
+        assert_eq!(cube_2x2.get_face_name(43), "l");
+        assert_eq!(cube_2x2.get_color("u".to_string()), Color::Yellow);
+        for algorithm in ["fi", "a_tech_right", "d_tech_right"] {
+            assert_eq!(cube_2x2.get_permutation(algorithm.to_string()), expected.get_permutation(algorithm.to_string()));
+        }
+
+        let tetrahedron = FileDefinition::load(format!("{}/tetrahedron_inflated_3x3.json", directory));
+        assert_eq!(tetrahedron.get_generators(), TetrahedronInflated3x3Definition::new().get_generators());
+        assert_eq!(tetrahedron.number_of_reachable_states(), BigUint::from(3_732_480u32));
+
+        let mut polyhedron = Polyhedron::create_polyhedron(cube_2x2);
+        polyhedron.rotate_many(vec!["f", "a_tech_right"]);
+        polyhedron.print_polyhedron();
+        Polyhedron::create_polyhedron(tetrahedron).print_polyhedron();
+    }
+
+    #[test]
+    fn invalid_file_definitions() {
+        let definition = |faces: &str, moves: &str| format!(r#"{{"name": "test", "faces": [{}], "moves": [{}]}}"#, faces, moves);
+        let face = r#"{"name": "f", "color": "red", "facelets": [1, 2, 3]}"#;
+
+        assert!(FileDefinition::try_from_json(&definition(face, r#"{"name": "f", "cycles": [[1, 2, 3]]}"#)).is_ok());
+        assert!(matches!(FileDefinition::try_from_json(&definition(face, r#"{"name": "f", "cycles": [[1, 2, 4]]}"#)),
+            Err(DefinitionError::NotAFacelet { facelet: 4, .. })));
+        assert!(matches!(FileDefinition::try_from_json(&definition(face, r#"{"name": "f", "cycles": [[1, 2, 1]]}"#)),
+            Err(DefinitionError::Permutation { .. })));
+        assert!(matches!(FileDefinition::try_from_json(&definition(&face.replace("red", "plaid"), "")),
+            Err(DefinitionError::UnknownColor(_))));
+        assert!(matches!(FileDefinition::try_from_json("{}"), Err(DefinitionError::Parse(_))));
+
+        let toml = "name = \"test\"\nmoves = []\n[[faces]]\nname = \"f\"\ncolor = \"red\"\nfacelets = [1, 2]\n";
+        assert!(FileDefinition::try_from_toml(toml).is_ok());
+        assert!(matches!(FileDefinition::try_from_toml(&format!("{}[[algorithms]]\nname = \"a\"\nmoves = \"f\"\n", toml)),
+            Err(DefinitionError::UnknownMove { .. })));
+        assert!(matches!(FileDefinition::try_from_toml(&format!("{}[start_state]\n1 = 2\n", toml)),
+            Err(DefinitionError::InvalidStartState(_))));
+        assert!(FileDefinition::try_from_toml(&format!("{}[start_state]\n1 = 2\n2 = 1\n", toml)).is_ok());
+        assert!(FileDefinition::try_from_toml(&format!("layout = \"{{1}} {{2}} {{x}}\"\n{}", toml)).is_ok());
+        assert!(matches!(FileDefinition::try_from_toml(&format!("layout = \"{{1}} {{99}}\"\n{}", toml)),
+            Err(DefinitionError::LayoutNotAFacelet(99))));
+        assert!(matches!(FileDefinition::try_load("definitions/cube_2x2.yaml"), Err(DefinitionError::Io(_))));
+
+        // Moves, generated inverses and algorithms share their names
+        let moves = |moves: &str| definition(face, moves);
+        assert!(matches!(FileDefinition::try_from_json(&moves(r#"{"name": "f", "cycles": [[1, 2]]}, {"name": "f", "cycles": [[2, 3]]}"#)),
+            Err(DefinitionError::DuplicateName(name)) if name == "f"));
+        assert!(matches!(FileDefinition::try_from_json(&moves(r#"{"name": "f", "cycles": [[1, 2]]}, {"name": "fi", "cycles": [[2, 3]]}"#)),
+            Err(DefinitionError::DuplicateName(name)) if name == "fi"));
+        assert!(matches!(FileDefinition::try_from_json(&moves(r#"{"name": "fi", "cycles": [[1, 2]]}, {"name": "f", "cycles": [[2, 3]]}"#)),
+            Err(DefinitionError::DuplicateName(name)) if name == "fi"));
+        let algorithm = "name = \"test\"\n[[faces]]\nname = \"f\"\ncolor = \"red\"\nfacelets = [1, 2]\n\
+            [[moves]]\nname = \"f\"\ncycles = [[1, 2]]\n[[algorithms]]\nname = \"f\"\nmoves = \"fi fi\"\n";
+        assert!(matches!(FileDefinition::try_from_toml(algorithm),
+            Err(DefinitionError::DuplicateName(name)) if name == "f"));
+    }
+
+    #[test]
+    fn ksolve_definitions() {
+        let cube_2x2 = Cube2x2Definition::new();
+        let text = ksolve::to_ksolve(&cube_2x2, "Cube 2x2").unwrap();
+        assert!(text.starts_with("Name Cube 2x2\n\nSet ORBIT1 8 3\n"));
+
+        let imported = ksolve::from_ksolve(&text).unwrap();
+        assert_eq!(imported.name(), "Cube 2x2");
+        assert_eq!(imported.get_generators().len(), 6);
+        assert_eq!(imported.number_of_reachable_states(), cube_2x2.number_of_reachable_states());
+        assert_eq!(ksolve::to_ksolve(&imported, "Cube 2x2").unwrap(), text);
+
+        let tetrahedron = TetrahedronInflated3x3Definition::new();
+        let imported = ksolve::from_ksolve(&ksolve::to_ksolve(&tetrahedron, "Tetrahedron").unwrap()).unwrap();
+        assert_eq!(imported.get_pieces().len(), 10);
+        assert_eq!(imported.number_of_reachable_states(), BigUint::from(3_732_480u32));
+
+        // Three pieces of two orientations cycled by a, one of them flipped on the way
+        let text = "# comment\nName Toy\nSet PIECES 3 2\nIgnore\nPIECES\n0 0 0\nEnd\nMove a\nPIECES\n2 3 1\n0 0 1\nEnd\n";
+        let toy = ksolve::from_ksolve(text).unwrap();
+        assert_eq!(toy.get_permutation("a".to_string()).cycles(), vec![vec![1, 3, 5, 2, 4, 6]]);
+        assert_eq!(toy.get_face_name(4), "PIECES_2");
+
+        let mut polyhedron = Polyhedron::create_polyhedron(toy);
+        polyhedron.rotate_many(vec!["a", "a", "a", "ai", "ai", "ai"]);
+        assert_eq!(polyhedron.current_state, polyhedron.solved_state);
+
+        assert!(matches!(ksolve::from_ksolve("Set PIECES 3 2\nMove a\nPIECES\n2 3 4\nEnd\n"),
+            Err(DefinitionError::Syntax { line: 4, .. })));
+        assert!(matches!(ksolve::from_ksolve("Set PIECES 3 2\nMove a\nPIECES\n2 2 1\nEnd\n"),
+            Err(DefinitionError::Permutation { .. })));
+        assert!(matches!(ksolve::from_ksolve("Set PIECES 3 2\nMove a\nOTHER\n"), Err(DefinitionError::Syntax { line: 3, .. })));
+        assert!(matches!(ksolve::from_ksolve("Set PIECES 3 2\nBlock\nPIECES 1 2\nEnd\n"), Err(DefinitionError::Syntax { line: 2, .. })));
+
+        // The start state is the state after a, so ai solves it
+        let started = ksolve::from_ksolve(&format!("{}StartState\nPIECES\n2 3 1\n0 0 1\nEnd\n", text)).unwrap();
+        let mut polyhedron = Polyhedron::create_polyhedron(started);
+        assert_ne!(polyhedron.current_state, polyhedron.solved_state);
+        polyhedron.rotate("ai");
+        assert_eq!(polyhedron.current_state, polyhedron.solved_state);
+        assert!(matches!(ksolve::from_ksolve("Set PIECES 3 2\nSolved\nPIECES\n1 1 2\nEnd\nStartState\nPIECES\n1 2 2\nEnd\n"),
+            Err(DefinitionError::Syntax { line: 6, .. })));
+
+        // Two corners that can be swapped, the mirror s of a corner isn't a twist
+        let corners = |moves: &str| format!(r#"{{"name": "corners", "faces": [{{"name": "f", "color": "red", "facelets": [1, 2, 3, 4, 5, 6]}}],
+            "moves": [{{"name": "t", "cycles": [[1, 2, 3]]}}, {{"name": "m", "cycles": [[1, 4], [2, 5], [3, 6]]}}{}]}}"#, moves);
+        assert!(ksolve::to_ksolve(&FileDefinition::try_from_json(&corners("")).unwrap(), "corners").is_ok());
+        let mirror = FileDefinition::try_from_json(&corners(r#", {"name": "s", "cycles": [[2, 3]]}"#)).unwrap();
+        assert!(matches!(ksolve::to_ksolve(&mirror, "mirror"), Err(DefinitionError::NotExportable(_))));
+    }
+
+    #[test]
+    fn definitions_are_valid() {
+        assert!(Cube2x2Definition::try_new().is_ok());
+        assert!(TetrahedronInflated3x3Definition::try_new().is_ok());
+    }
+
+    #[test]
+    fn group_order() {
+        let cube_2x2 = Cube2x2Definition::new();
+        let group = PermutationGroup::create_group(cube_2x2.get_generators());
+
+        assert_eq!(group.order(), BigUint::from(3_674_160u32 * 24));
+        assert!(group.contains(cube_2x2.get_permutation("a_tech_right".to_string())));
+        assert!(group.contains(cube_2x2.get_permutation("d_tech_right".to_string())));
+
+        let tetrahedron = TetrahedronInflated3x3Definition::new();
+        let group = PermutationGroup::create_group(tetrahedron.get_generators());
+
+        assert_eq!(group.order(), BigUint::from(3_732_480u32));
+    }
+
+    #[test]
+    fn number_of_reachable_states() {
+        let cube_2x2 = Cube2x2Definition::new();
+        let tetrahedron = TetrahedronInflated3x3Definition::new();
+
+        assert_eq!(cube_2x2.number_of_reachable_states(), BigUint::from(3_674_160u32 * 24));
+        assert_eq!(tetrahedron.number_of_reachable_states(), BigUint::from(3_732_480u32));
+    }
+
+    #[test]
+    fn structure_report() {
+        let report = TetrahedronInflated3x3Definition::new().structure_report();
+        let (corners, edges) = (&report.orbits()[0], &report.orbits()[1]);
+
+        assert_eq!(corners.blocks().len(), 4);
+        assert_eq!((corners.block_action_name(), corners.kernel_name()), ("A4".to_string(), "C3^3".to_string()));
+        assert_eq!(edges.blocks().len(), 6);
+        assert_eq!((edges.block_action_name(), edges.kernel_name()), ("A6".to_string(), "C2^5".to_string()));
+
+        // Both kinds of pieces are only evenly permuted, but independently of each other
+        assert_eq!(report.parity_constraints(), &vec![vec![0], vec![1]]);
+        assert!(report.is_direct_product());
+        assert!(report.to_string().contains("subgroup of index 2 of C2 wr A6"));
+
+        let report = Cube2x2Definition::new().structure_report();
+        assert_eq!(report.orbits()[0].kernel_name(), "C3^7");
+    }
 }
@@ -1,7 +1,15 @@
 use std::collections::{HashMap};
 use colorful::{Color};
 
-use permutations::Permutation;
+use permutations::action::ActionHomomorphism;
+use permutations::block::smallest_blocks;
+use permutations::group::PermutationGroup;
+use permutations::notation::{parse_word, ParseWordError};
+use permutations::orbit::orbits;
+use permutations::relations::{find_relations, Relations};
+use permutations::structure::StructureReport;
+use permutations::word::Word;
+use permutations::{BigUint, Permutation};
 
 pub trait PolyhedronDefinition {
     fn start_state(&self) -> HashMap<usize, usize>;
@@ -42,4 +50,56 @@ pub trait PolyhedronDefinition {
                         current_state: &HashMap<usize, usize>);
 
     fn get_permutation(&self, permutation_name: String) -> &Permutation;
-}
\ No newline at end of file
+
+    /// Returns the base moves of the puzzle, every reachable state being a product of them.
+    fn get_generators(&self) -> Vec<&Permutation>;
+
+    /// Returns the facelets of every piece, derived from the moves: the facelets of a piece always move together.
+    /// Each orbit of facelets is split in its smallest blocks, a facelet that moves alone being a piece by itself.
+    fn get_pieces(&self) -> Vec<Vec<usize>> {
+        let facelets = self.solved_state();
+
+        return orbits(self.get_generators()).into_iter()
+            .filter(|orbit| facelets.contains_key(&orbit[0]))
+            .flat_map(|orbit| smallest_blocks(orbit[0], self.get_generators()))
+            .collect();
+    }
+
+    /// Returns the action of the moves on the pieces, numbered in the order of `get_pieces`:
+    /// it maps a state to the permutation of the pieces, ignoring their orientations.
+    fn piece_action(&self) -> ActionHomomorphism {
+        return ActionHomomorphism::create_block_action(self.get_pieces());
+    }
+
+    /// Returns the number of states reachable from the solved state, i.e. the order of the group of the moves.
+    /// Computed with the randomized Schreier-Sims algorithm, so that it stays fast for the big puzzles: the result is
+    /// exact with probability at least 1 - 2^-40, and is otherwise an undercount, a proper divisor of the number of states.
+    /// `PermutationGroup::create_group` gives the exact number for sure.
+    fn number_of_reachable_states(&self) -> BigUint {
+        return PermutationGroup::create_group_randomized(self.get_generators(), 0).order();
+    }
+
+    /// Returns a report of the structure of the group of the moves: the pieces, how they can be permuted and oriented,
+    /// and the parity constraints between the kinds of pieces. Printed with `Display`.
+    fn structure_report(&self) -> StructureReport {
+        return StructureReport::create_report(self.get_generators());
+    }
+
+    /// Returns relations among the base moves, looked for with words of at most max_length moves,
+    /// together with the moves that are the identity, equal or inverse of each other, a sign of a wrong definition.
+    fn find_relations(&self, max_length: usize) -> Relations {
+        return find_relations(self.get_generators(), max_length, 2_000);
+    }
+
+    /// Parses moves written in the usual notation, e.g. `R U R' U2 (R U)3 [R, U] [F: R U R']`.
+    /// The move names are the names of the base moves, a name that is not one of them being looked up in lowercase,
+    /// so that `R` is the move `r` of the 2x2.
+    fn parse_moves(&self, text: &str) -> Result<Word, ParseWordError> {
+        let names: Vec<String> = self.get_generators().iter().filter_map(|g| g.name()).collect();
+
+        return parse_word(text, |name| {
+            let lowercase = name.to_lowercase();
+            names.iter().find(|&known| known == name).or_else(|| names.iter().find(|&known| *known == lowercase)).cloned()
+        });
+    }
+}
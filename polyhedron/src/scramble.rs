@@ -0,0 +1,134 @@
+//! Scrambles reproducible from a seed, written with the names of the base moves, e.g. `f r' u2 d`.
+//!
+//! A random-move scramble never turns a move again while the moves played since its last turn all commute with it,
+//! like `f f` or `f b f`, so that no move cancels or merges with an earlier one. A random-state scramble leads to a state drawn
+//! uniformly among the reachable states, using the stabilizer chain of the group of the moves.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use permutations::group::PermutationGroup;
+use permutations::Permutation;
+use permutations::word::Word;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrambleKind {
+    /// The specified number of random moves, a power of a base move counting as one move.
+    RandomMoves(usize),
+    /// The moves leading to a random state. They are not necessarily short.
+    RandomState,
+}
+
+pub struct Scramble {
+    moves: Word,
+    state: HashMap<usize, usize>,
+}
+
+impl Scramble {
+    pub fn create_scramble(moves: Word, state: HashMap<usize, usize>) -> Self {
+        return Self { moves, state };
+    }
+
+    /// Returns the moves of the scramble, printed in the notation of the definition by `Display`.
+    pub fn moves(&self) -> &Word {
+        return &self.moves;
+    }
+
+    /// Returns the state reached by the moves from the start state.
+    pub fn state(&self) -> &HashMap<usize, usize> {
+        return &self.state;
+    }
+}
+
+/// Returns length random moves of the generators, unnamed generators being named g0, g1... by their index.
+/// The scramble is shorter if the generators don't allow length moves without cancellations, e.g. with a single generator.
+pub fn random_moves(generators: Vec<&Permutation>, length: usize, seed: u64) -> Word {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let names = generator_names(&generators);
+
+    let orders = move_orders(&generators);
+    let commuting: Vec<Vec<bool>> = generators.iter()
+        .map(|a| generators.iter().map(|b| commute(a, b)).collect())
+        .collect();
+
+    let mut played: Vec<usize> = Vec::new();
+    for _ in 0..length {
+        // A move merges with an earlier turn of the same move if it commutes with every move played since
+        let allowed: Vec<usize> = (0..generators.len())
+            .filter(|&i| orders[i] > 1)
+            .filter(|&i| {
+                played.iter().rev()
+                    .take_while(|&&j| commuting[i][j])
+                    .all(|&j| j != i)
+            })
+            .collect();
+
+        if allowed.is_empty() {
+            break;
+        }
+
+        played.push(allowed[rng.random_range(0..allowed.len())]);
+    }
+
+    let letters = played.into_iter()
+        .map(|i| letter(names[i].clone(), rng.random_range(1..orders[i] as u64) as i64, orders[i]))
+        .collect();
+
+    return sequence(letters);
+}
+
+/// Returns moves of the generators leading to a state drawn uniformly among the states they can reach.
+pub fn random_state(generators: Vec<&Permutation>, seed: u64) -> Word {
+    let names = generator_names(&generators);
+    let named: Vec<Permutation> = generators.iter().zip(&names)
+        .map(|(g, name)| (*g).clone().with_word(Word::generator(name)))
+        .collect();
+
+    let group = PermutationGroup::create_group(named.iter().collect());
+    let target = group.random_element(&mut StdRng::seed_from_u64(seed));
+    let word = group.factorize_word(&target).unwrap();
+
+    let orders: HashMap<&String, i64> = names.iter().zip(move_orders(&generators)).collect();
+    let letters = word.letters().into_iter()
+        .map(|(name, k)| {
+            let order = orders[&name];
+            letter(name, k.rem_euclid(order), order)
+        })
+        .filter(|word| *word != Word::Identity)
+        .collect();
+
+    return sequence(letters);
+}
+
+fn generator_names(generators: &[&Permutation]) -> Vec<String> {
+    return generators.iter().enumerate()
+        .map(|(i, g)| g.name().unwrap_or_else(|| format!("g{}", i)))
+        .collect();
+}
+
+/// The orders of the moves, an order that doesn't fit in an i64 being replaced by i64::MAX:
+/// the exponents of such a move are drawn below it and are never reduced.
+fn move_orders(generators: &[&Permutation]) -> Vec<i64> {
+    return generators.iter().map(|g| i64::try_from(g.order()).unwrap_or(i64::MAX)).collect();
+}
+
+fn commute(a: &Permutation, b: &Permutation) -> bool {
+    return a.compose(b) == b.compose(a);
+}
+
+/// Returns the power k of the move, written with the smallest exponent: `f3` is written `f'` for a move of order 4.
+fn letter(name: String, k: i64, order: i64) -> Word {
+    let k = if k > order / 2 { k - order } else { k };
+
+    return Word::generator(&name).pow(k);
+}
+
+fn sequence(mut letters: Vec<Word>) -> Word {
+    return match letters.len() {
+        0 => Word::Identity,
+        1 => letters.pop().unwrap(),
+        _ => Word::Sequence(letters),
+    };
+}